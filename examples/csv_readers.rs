@@ -0,0 +1,95 @@
+use std::time::{Duration, Instant};
+use training_rust_pipeline::models::csv_byte_reader::CsvByteReader;
+use training_rust_pipeline::models::csv_dialect::CsvDialect;
+use training_rust_pipeline::models::csv_reader::CsvReader;
+use training_rust_pipeline::models::csv_split_reader::SplitCsvReader;
+use training_rust_pipeline::models::error::EtlResult;
+use training_rust_pipeline::models::input::InputPort;
+
+// À lancer en release : cargo run --release --example csv_readers
+const PATHS: [&str; 3] = [
+    "./src/data/data_1.csv",
+    "./src/data/data_4.csv",
+    "./src/data/data_5.csv"
+];
+const ROUNDS: usize = 10;
+const CHUNK_SIZE: usize = 1000;
+
+// Renvoie le nombre de records et d'octets lus, pour que rien ne soit optimisé
+fn bench(name: &str, read: impl Fn(&str) -> EtlResult<(usize, usize)>) -> EtlResult<()> {
+    let mut best = Duration::MAX;
+    let mut totals = (0, 0);
+
+    for _ in 0..ROUNDS {
+        let start = Instant::now();
+        totals = (0, 0);
+        for path in PATHS {
+            let (records, bytes) = read(path)?;
+            totals.0 += records;
+            totals.1 += bytes;
+        }
+        best = best.min(start.elapsed());
+    }
+
+    println!("{:<32} {:>8} records {:>10} bytes {:>10.2?}", name, totals.0, totals.1, best);
+    Ok(())
+}
+
+fn main() -> EtlResult<()> {
+    println!("Meilleur temps sur {} passes", ROUNDS);
+
+    bench("CsvReader (StringRecord)", |path| {
+        let mut reader = CsvReader::new(path, CHUNK_SIZE)?;
+        let mut totals = (0, 0);
+        while let Some(chunk) = reader.read_chunk()? {
+            totals.0 += chunk.len();
+            totals.1 += chunk.iter().map(|record| record.as_slice().len()).sum::<usize>();
+        }
+        Ok(totals)
+    })?;
+
+    bench("CsvByteReader (ByteRecord)", |path| {
+        let mut reader = CsvByteReader::new(path, CHUNK_SIZE)?;
+        let mut chunk = Vec::new();
+        let mut totals = (0, 0);
+        while reader.read_byte_chunk(&mut chunk)? {
+            totals.0 += chunk.len();
+            totals.1 += chunk.iter().map(|record| record.as_slice().len()).sum::<usize>();
+        }
+        Ok(totals)
+    })?;
+
+    bench("CsvByteReader (2 columns)", |path| {
+        let mut reader = CsvByteReader::new(path, CHUNK_SIZE)?.with_column_indices(vec![0, 3]);
+        let mut chunk = Vec::new();
+        let mut totals = (0, 0);
+        while reader.read_byte_chunk(&mut chunk)? {
+            totals.0 += chunk.len();
+            totals.1 += chunk.iter().map(|record| record.as_slice().len()).sum::<usize>();
+        }
+        Ok(totals)
+    })?;
+
+    bench("CsvByteReader (2 columns, UTF-8)", |path| {
+        let mut reader = CsvByteReader::new(path, CHUNK_SIZE)?.with_column_indices(vec![0, 3]);
+        let mut totals = (0, 0);
+        while let Some(chunk) = reader.read_chunk()? {
+            totals.0 += chunk.len();
+            totals.1 += chunk.iter().map(|record| record.as_slice().len()).sum::<usize>();
+        }
+        Ok(totals)
+    })?;
+
+    bench("SplitCsvReader (StringRecord)", |path| {
+        let parallelism = std::thread::available_parallelism().map_or(1, |n| n.get());
+        let mut reader = SplitCsvReader::new(path, CHUNK_SIZE, &CsvDialect::default(), parallelism)?;
+        let mut totals = (0, 0);
+        while let Some(chunk) = reader.read_chunk()? {
+            totals.0 += chunk.len();
+            totals.1 += chunk.iter().map(|record| record.as_slice().len()).sum::<usize>();
+        }
+        Ok(totals)
+    })?;
+
+    Ok(())
+}
@@ -1 +1,2 @@
+pub mod storage_input;
 pub mod storage_output;
\ No newline at end of file
@@ -0,0 +1,383 @@
+use std::io::{BufRead, BufReader, Read};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use crate::models::error::{EtlError, EtlResult};
+use crate::models::input::InputPort;
+use crate::utils::compression::open_reader;
+use crate::utils::encoding::EncodingOptions;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JsonLayout {
+    // Un tableau d'objets : `[{...}, {...}]`
+    Array,
+    // Un objet par ligne
+    Lines,
+}
+
+// Associe une colonne du record à un JSON pointer (RFC 6901), ex: `/user/login`,
+// ou pour une source xml à un chemin relatif au record (`path` accepté aussi)
+#[derive(Debug, Deserialize, Clone)]
+pub struct FieldMapping {
+    pub name: String,
+    #[serde(alias = "path")]
+    pub pointer: String,
+}
+
+pub struct JsonReader {
+    path: String,
+    reader: BufReader<Box<dyn Read + Send>>,
+    layout: JsonLayout,
+    chunk_size: usize,
+    fields: Vec<FieldMapping>,
+    // Colonnes déduites du premier objet quand aucun mapping n'est fourni
+    columns: Option<Vec<String>>,
+    started: bool,
+    finished: bool,
+    index: usize,
+    line: usize,
+    errors: Vec<String>,
+    replaced_chars: Arc<AtomicUsize>,
+}
+
+impl JsonReader {
+    pub fn new(path: &str, layout: JsonLayout, fields: Vec<FieldMapping>, chunk_size: usize) -> EtlResult<Self> {
+        JsonReader::with_encoding(path, layout, fields, chunk_size, &EncodingOptions::default())
+    }
+
+    pub fn with_encoding(path: &str, layout: JsonLayout, fields: Vec<FieldMapping>, chunk_size: usize, encoding: &EncodingOptions) -> EtlResult<Self> {
+        let decoded = encoding.decode(path, open_reader(path)?)?;
+
+        Ok(JsonReader {
+            path: path.to_string(),
+            reader: BufReader::new(decoded.reader),
+            layout,
+            chunk_size,
+            fields,
+            columns: None,
+            started: false,
+            finished: false,
+            index: 0,
+            line: 0,
+            errors: Vec::new(),
+            replaced_chars: decoded.replaced_chars,
+        })
+    }
+
+    // Noms des colonnes produites, connus après le premier record sans mapping
+    pub fn headers(&self) -> Option<csv::StringRecord> {
+        if !self.fields.is_empty() {
+            return Some(self.fields.iter().map(|f| f.name.as_str()).collect());
+        }
+        self.columns.as_ref().map(|columns| columns.iter().collect())
+    }
+
+    fn next_value(&mut self) -> EtlResult<Option<Value>> {
+        match self.layout {
+            JsonLayout::Lines => self.next_line_value(),
+            JsonLayout::Array => self.next_array_value(),
+        }
+    }
+
+    fn next_line_value(&mut self) -> EtlResult<Option<Value>> {
+        let mut line = String::new();
+
+        loop {
+            line.clear();
+            let read = self.reader.read_line(&mut line)
+                .map_err(|err| EtlError::io(&self.path, err))?;
+            if read == 0 {
+                return Ok(None);
+            }
+            self.line += 1;
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            match serde_json::from_str(&line) {
+                Ok(value) => return Ok(Some(value)),
+                Err(err) => {
+                    self.errors.push(format!("{} record parse error: line {}: invalid JSON: {}", self.index, self.line, err));
+                    self.index += 1;
+                }
+            }
+        }
+    }
+
+    fn next_array_value(&mut self) -> EtlResult<Option<Value>> {
+        loop {
+            if !self.started {
+                self.started = true;
+                match self.next_significant_byte()? {
+                    Some(b'[') => {}
+                    _ => return Err(self.syntax_error("le document doit être un tableau JSON")),
+                }
+                if self.peek_significant_byte()? == Some(b']') {
+                    self.next_significant_byte()?;
+                    return Ok(None);
+                }
+            } else {
+                match self.next_significant_byte()? {
+                    Some(b',') => {}
+                    Some(b']') | None => return Ok(None),
+                    Some(other) => return Err(self.syntax_error(&format!("`,` attendu, `{}` trouvé", other as char))),
+                }
+            }
+
+            // Un élément invalide est signalé puis sauté, comme une ligne NDJSON
+            let element = self.read_array_element()?;
+            match serde_json::from_slice(&element) {
+                Ok(value) => return Ok(Some(value)),
+                Err(err) => {
+                    self.errors.push(format!("{} record parse error: invalid JSON: {}", self.index, err));
+                    self.index += 1;
+                }
+            }
+        }
+    }
+
+    // Copie les octets d'un élément, sans charger le reste du tableau, jusqu'au
+    // `,` ou `]` qui le termine (laissé dans le buffer), hors chaînes et imbrications
+    fn read_array_element(&mut self) -> EtlResult<Vec<u8>> {
+        let mut element = Vec::new();
+        let mut depth = 0usize;
+        let mut in_string = false;
+        let mut escaped = false;
+
+        loop {
+            let buffer = self.reader.fill_buf().map_err(|err| EtlError::io(&self.path, err))?;
+            if buffer.is_empty() {
+                return Ok(element);
+            }
+
+            let mut used = 0;
+            let mut complete = false;
+            for &byte in buffer {
+                if in_string {
+                    match byte {
+                        _ if escaped => escaped = false,
+                        b'\\' => escaped = true,
+                        b'"' => in_string = false,
+                        _ => {}
+                    }
+                } else {
+                    match byte {
+                        b'"' => in_string = true,
+                        b'{' | b'[' => depth += 1,
+                        b'}' | b']' if depth > 0 => depth -= 1,
+                        b',' | b']' if depth == 0 => {
+                            complete = true;
+                            break;
+                        },
+                        _ => {}
+                    }
+                }
+                used += 1;
+            }
+
+            element.extend_from_slice(&buffer[..used]);
+            self.reader.consume(used);
+            if complete {
+                return Ok(element);
+            }
+        }
+    }
+
+    fn peek_significant_byte(&mut self) -> EtlResult<Option<u8>> {
+        loop {
+            let buffer = self.reader.fill_buf().map_err(|err| EtlError::io(&self.path, err))?;
+            match buffer.first() {
+                None => return Ok(None),
+                Some(byte) if byte.is_ascii_whitespace() => self.reader.consume(1),
+                Some(byte) => return Ok(Some(*byte)),
+            }
+        }
+    }
+
+    fn next_significant_byte(&mut self) -> EtlResult<Option<u8>> {
+        let byte = self.peek_significant_byte()?;
+        if byte.is_some() {
+            let mut buffer = [0; 1];
+            self.reader.read_exact(&mut buffer).map_err(|err| EtlError::io(&self.path, err))?;
+        }
+        Ok(byte)
+    }
+
+    fn syntax_error(&self, reason: &str) -> EtlError {
+        EtlError::InvalidSource { path: self.path.clone(), reason: reason.to_string() }
+    }
+
+    fn make_record(&mut self, value: Value) -> Option<csv::StringRecord> {
+        let index = self.index;
+        self.index += 1;
+
+        if !value.is_object() {
+            self.errors.push(format!("{} record parse error: l'élément n'est pas un objet", index));
+            return None;
+        }
+
+        if !self.fields.is_empty() {
+            return Some(self.fields.iter()
+                .map(|field| value.pointer(&field.pointer).map(value_to_string).unwrap_or_default())
+                .collect());
+        }
+
+        let mut flat = Map::new();
+        flatten("", &value, &mut flat);
+
+        let columns = self.columns.get_or_insert_with(|| flat.keys().cloned().collect());
+
+        // Les colonnes viennent du premier objet : une clé apparue ensuite serait perdue
+        let unknown: Vec<&str> = flat.keys()
+            .filter(|key| !columns.contains(key))
+            .map(String::as_str)
+            .collect();
+        if !unknown.is_empty() {
+            self.errors.push(format!(
+                "{} record parse error: clés absentes du premier objet: {}", index, unknown.join(", ")
+            ));
+            return None;
+        }
+
+        Some(columns.iter()
+            .map(|column| flat.get(column).map(value_to_string).unwrap_or_default())
+            .collect())
+    }
+}
+
+impl InputPort for JsonReader {
+    fn read_chunk(&mut self) -> EtlResult<Option<Vec<csv::StringRecord>>> {
+        let mut chunk = Vec::new();
+
+        while !self.finished && chunk.len() < self.chunk_size {
+            match self.next_value()? {
+                Some(value) => {
+                    if let Some(record) = self.make_record(value) {
+                        chunk.push(record);
+                    }
+                }
+                None => self.finished = true,
+            }
+        }
+
+        if chunk.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(chunk))
+        }
+    }
+
+    fn take_errors(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.errors)
+    }
+
+    fn take_replaced_chars(&mut self) -> usize {
+        self.replaced_chars.swap(0, Ordering::Relaxed)
+    }
+}
+
+// `{"a": {"b": 1}, "c": [2]}` devient `{"a.b": 1, "c.0": 2}`
+fn flatten(prefix: &str, value: &Value, out: &mut Map<String, Value>) {
+    let key = |name: &str| if prefix.is_empty() { name.to_string() } else { format!("{}.{}", prefix, name) };
+
+    match value {
+        Value::Object(map) => {
+            for (name, child) in map {
+                flatten(&key(name), child, out);
+            }
+        },
+        Value::Array(items) => {
+            for (idx, child) in items.iter().enumerate() {
+                flatten(&key(&idx.to_string()), child, out);
+            }
+        },
+        scalar => {
+            out.insert(prefix.to_string(), scalar.clone());
+        }
+    }
+}
+
+fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_tmp(name: &str, content: &str) -> String {
+        let path = std::env::temp_dir().join(name);
+        std::fs::write(&path, content).unwrap();
+        path.to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_json_array_flattened() {
+        let path = write_tmp("json_reader_array.json", r#"[
+            {"username": "jdupont", "id": 1, "name": {"first": "Jean", "last": "Dupont"}},
+            {"username": "mmartin", "id": 2, "name": {"first": "Marie", "last": null}},
+            {"username": "pdurand", "id": 3, "name": {"first": "Paul", "last": "Durand"}}
+        ]"#);
+
+        let mut reader = JsonReader::new(&path, JsonLayout::Array, Vec::new(), 2).unwrap();
+
+        let first = reader.read_chunk().unwrap().unwrap();
+        let second = reader.read_chunk().unwrap().unwrap();
+
+        assert_eq!(first.len(), 2);
+        assert_eq!(second.len(), 1);
+        assert!(reader.read_chunk().unwrap().is_none());
+        assert_eq!(reader.headers().unwrap(), vec!["username", "id", "name.first", "name.last"]);
+        assert_eq!(first[1], vec!["mmartin", "2", "Marie", ""]);
+    }
+
+    #[test]
+    fn test_json_array_skips_bad_elements() {
+        let path = write_tmp("json_reader_scalars.json", concat!(
+            r#"[{"login":"a","ville":"x"}, 5, "b,]", {"login": tru}, [1, {"x": "]"}], {"login":"b","ville":"y"}, "#,
+            r#"{"login":"c","ville":"z","pays":"fr"}]"#
+        ));
+
+        let mut reader = JsonReader::new(&path, JsonLayout::Array, Vec::new(), 10).unwrap();
+        let chunk = reader.read_chunk().unwrap().unwrap();
+
+        assert_eq!(chunk, vec![vec!["a", "x"], vec!["b", "y"]]);
+        let errors = reader.take_errors();
+        assert_eq!(errors.len(), 5);
+        assert!(errors[0].starts_with("1 record parse error"));
+        assert!(errors[2].starts_with("3 record parse error: invalid JSON"));
+        assert_eq!(errors[4], "6 record parse error: clés absentes du premier objet: pays");
+    }
+
+    #[test]
+    fn test_ndjson_with_pointers() {
+        let path = write_tmp("json_reader_lines.ndjson", concat!(
+            r#"{"user": {"login": "jdupont", "uuid": "a-1"}, "profile": {"names": ["Jean", "Dupont"]}}"#, "\n",
+            "not json\n",
+            "\n",
+            r#"{"user": {"login": "mmartin", "uuid": "a-2"}, "profile": {"names": ["Marie", "Martin"]}}"#, "\n",
+        ));
+
+        let fields = vec![
+            FieldMapping { name: "username".into(), pointer: "/user/login".into() },
+            FieldMapping { name: "identifier".into(), pointer: "/user/uuid".into() },
+            FieldMapping { name: "first_name".into(), pointer: "/profile/names/0".into() },
+            FieldMapping { name: "last_name".into(), pointer: "/profile/names/1".into() },
+        ];
+
+        let mut reader = JsonReader::new(&path, JsonLayout::Lines, fields, 10).unwrap();
+        let chunk = reader.read_chunk().unwrap().unwrap();
+
+        assert_eq!(chunk.len(), 2);
+        assert_eq!(chunk[1], vec!["mmartin", "a-2", "Marie", "Martin"]);
+        let errors = reader.take_errors();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].starts_with("1 record parse error: line 2: invalid JSON"));
+    }
+}
@@ -0,0 +1,157 @@
+use std::io::{BufRead, BufReader, Read};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use regex::Regex;
+use crate::models::error::{EtlError, EtlResult};
+use crate::models::input::InputPort;
+use crate::utils::compression::open_reader;
+use crate::utils::encoding::EncodingOptions;
+
+// Formats de logs courants, utilisables à la place d'une regex dans la recette
+const COMMON_LOG: &str = r#"^(?P<host>\S+) (?P<ident>\S+) (?P<user>\S+) \[(?P<time>[^\]]+)\] "(?P<method>\S+) (?P<path>\S+)(?: (?P<protocol>[^"]*))?" (?P<status>\d{3}) (?P<size>\S+)"#;
+const COMBINED_LOG: &str = r#"^(?P<host>\S+) (?P<ident>\S+) (?P<user>\S+) \[(?P<time>[^\]]+)\] "(?P<method>\S+) (?P<path>\S+)(?: (?P<protocol>[^"]*))?" (?P<status>\d{3}) (?P<size>\S+) "(?P<referer>[^"]*)" "(?P<user_agent>[^"]*)""#;
+const SYSLOG: &str = r"^(?P<time>\w{3} [ \d]\d \d{2}:\d{2}:\d{2}) (?P<host>\S+) (?P<program>[^:\[\s]+)(?:\[(?P<pid>\d+)\])?: (?P<message>.*)$";
+
+// `common` et `combined` : formats d'accès Apache/nginx ; `syslog` : format BSD (RFC 3164)
+pub fn preset_pattern(name: &str) -> Option<&'static str> {
+    match name {
+        "common" => Some(COMMON_LOG),
+        "combined" => Some(COMBINED_LOG),
+        "syslog" => Some(SYSLOG),
+        _ => None
+    }
+}
+
+// Une ligne = un record, les champs sont les groupes nommés de la regex
+pub struct LogReader {
+    path: String,
+    reader: BufReader<Box<dyn Read + Send>>,
+    regex: Regex,
+    chunk_size: usize,
+    line: Vec<u8>,
+    index: usize,
+    errors: Vec<String>,
+    unmatched_lines: usize,
+    replaced_chars: Arc<AtomicUsize>,
+}
+
+impl LogReader {
+    pub fn new(path: &str, pattern: &str, chunk_size: usize, encoding: &EncodingOptions) -> EtlResult<Self> {
+        let regex = Regex::new(pattern)
+            .map_err(|err| EtlError::InvalidRecipe(format!("regex de log invalide: {}", err)))?;
+        if regex.capture_names().flatten().next().is_none() {
+            return Err(EtlError::InvalidRecipe("la regex de log doit avoir au moins un groupe nommé".to_string()));
+        }
+
+        let decoded = encoding.decode(path, open_reader(path)?)?;
+
+        Ok(LogReader {
+            path: path.to_string(),
+            reader: BufReader::new(decoded.reader),
+            regex,
+            chunk_size: chunk_size.max(1),
+            line: Vec::new(),
+            index: 0,
+            errors: Vec::new(),
+            unmatched_lines: 0,
+            replaced_chars: decoded.replaced_chars,
+        })
+    }
+
+    pub fn headers(&self) -> csv::StringRecord {
+        self.regex.capture_names().flatten().collect()
+    }
+
+    // Un groupe optionnel non capturé donne un champ vide
+    fn parse(&self, line: &str) -> Option<csv::StringRecord> {
+        let captures = self.regex.captures(line)?;
+        Some(self.regex.capture_names().flatten()
+            .map(|name| captures.name(name).map_or("", |m| m.as_str()))
+            .collect())
+    }
+}
+
+impl InputPort for LogReader {
+    fn read_chunk(&mut self) -> EtlResult<Option<Vec<csv::StringRecord>>> {
+        let mut chunk = Vec::new();
+
+        while chunk.len() < self.chunk_size {
+            self.line.clear();
+            // Lecture ou décompression impossible : la suite du fichier est perdue
+            let read = self.reader.read_until(b'\n', &mut self.line)
+                .map_err(|err| EtlError::io(&self.path, err))?;
+            if read == 0 {
+                break;
+            }
+
+            let line = self.line.strip_suffix(b"\n").unwrap_or(&self.line);
+            let line = line.strip_suffix(b"\r").unwrap_or(line);
+            if !line.is_empty() {
+                match std::str::from_utf8(line) {
+                    Ok(line) => match self.parse(line) {
+                        Some(record) => chunk.push(record),
+                        None => {
+                            self.unmatched_lines += 1;
+                            self.errors.push(format!("{} record parse error: line does not match pattern", self.index));
+                        },
+                    },
+                    Err(err) => self.errors.push(format!("{} record parse error: {}", self.index, err)),
+                }
+            }
+            self.index += 1;
+        }
+
+        if chunk.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(chunk))
+        }
+    }
+
+    fn take_errors(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.errors)
+    }
+
+    fn take_unmatched_lines(&mut self) -> usize {
+        std::mem::take(&mut self.unmatched_lines)
+    }
+
+    fn take_replaced_chars(&mut self) -> usize {
+        self.replaced_chars.swap(0, Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::stream_pipeline::StreamingPipeline;
+
+    #[test]
+    fn test_combined_log_preset() {
+        let path = std::env::temp_dir().join("log_reader_access.log");
+        std::fs::write(&path, concat!(
+            "127.0.0.1 - jdupont [10/Oct/2024:13:55:36 +0200] \"GET /index.html HTTP/1.1\" 200 2326 \"-\" \"curl/8.0\"\n",
+            "garbage line\n",
+            "10.0.0.2 - - [10/Oct/2024:13:55:37 +0200] \"POST /login HTTP/1.1\" 302 - \"https://example.org/\" \"Mozilla/5.0\"\n",
+        )).unwrap();
+
+        let pattern = preset_pattern("combined").unwrap();
+        let reader = LogReader::new(path.to_str().unwrap(), pattern, 1, &EncodingOptions::default()).unwrap();
+        assert_eq!(reader.headers().iter().take(3).collect::<Vec<_>>(), ["host", "ident", "user"]);
+
+        let mut records = Vec::new();
+        let stats = StreamingPipeline::from_input(reader)
+            .load(|chunk| {
+                records.extend_from_slice(chunk);
+                Ok(())
+            })
+            .unwrap();
+
+        assert_eq!(stats.total_filtered, 2);
+        assert_eq!(stats.unmatched_lines, 1);
+        assert_eq!(stats.errors(), ["1 record parse error: line does not match pattern"]);
+        assert_eq!(&records[1][5], "/login");
+        assert_eq!(&records[1][7], "302");
+        assert_eq!(&records[1][10], "Mozilla/5.0");
+    }
+}
@@ -0,0 +1,11 @@
+pub mod fixed_width;
+pub mod json;
+pub mod log;
+#[cfg(feature = "parquet")]
+pub mod parquet;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+#[cfg(feature = "xlsx")]
+pub mod xlsx;
+#[cfg(feature = "xml")]
+pub mod xml;
\ No newline at end of file
@@ -0,0 +1,102 @@
+use std::fs::File;
+use arrow_cast::display::{ArrayFormatter, FormatOptions};
+use parquet::arrow::arrow_reader::{ParquetRecordBatchReader, ParquetRecordBatchReaderBuilder};
+use crate::models::error::{EtlError, EtlResult};
+use crate::models::input::InputPort;
+
+pub struct ParquetReader {
+    path: String,
+    reader: ParquetRecordBatchReader,
+    headers: csv::StringRecord,
+}
+
+impl ParquetReader {
+    // Un chunk correspond à un batch de `chunk_size` lignes
+    pub fn new(path: &str, chunk_size: usize) -> EtlResult<Self> {
+        let file = File::open(path).map_err(|err| EtlError::io(path, err))?;
+
+        let builder = ParquetRecordBatchReaderBuilder::try_new(file)
+            .map_err(|err| EtlError::parquet(path, err))?;
+        let headers = builder.schema().fields().iter()
+            .map(|field| field.name().as_str())
+            .collect();
+        let reader = builder
+            .with_batch_size(chunk_size.max(1))
+            .build()
+            .map_err(|err| EtlError::parquet(path, err))?;
+
+        Ok(ParquetReader {
+            path: path.to_string(),
+            reader,
+            headers,
+        })
+    }
+
+    pub fn headers(&self) -> &csv::StringRecord {
+        &self.headers
+    }
+}
+
+impl InputPort for ParquetReader {
+    fn read_chunk(&mut self) -> EtlResult<Option<Vec<csv::StringRecord>>> {
+        let batch = match self.reader.next() {
+            Some(batch) => batch.map_err(|err| EtlError::InvalidSource { path: self.path.clone(), reason: err.to_string() })?,
+            None => return Ok(None),
+        };
+
+        // Toutes les colonnes sont rendues en texte, les nulls deviennent ""
+        let options = FormatOptions::default();
+        let formatters = batch.columns().iter()
+            .map(|column| ArrayFormatter::try_new(column.as_ref(), &options))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| EtlError::InvalidSource { path: self.path.clone(), reason: err.to_string() })?;
+
+        let chunk = (0..batch.num_rows())
+            .map(|row| {
+                formatters.iter()
+                    .map(|formatter| formatter.value(row).to_string())
+                    .collect::<csv::StringRecord>()
+            })
+            .collect();
+
+        Ok(Some(chunk))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapter::storage_output::parquet::{ParquetAdapter, ParquetOptions};
+    use crate::models::output::OutputPort;
+    use crate::models::stream_pipeline::StreamingPipeline;
+    use crate::models::user::User;
+    use crate::utils::set_user::generate_user;
+
+    #[test]
+    fn test_parquet_round_trip() {
+        let path = std::env::temp_dir().join("parquet_round_trip.parquet");
+        let path = path.to_str().unwrap();
+
+        let options = ParquetOptions { row_group_size: 100, ..ParquetOptions::default() }
+            .with_compression("zstd", Some(3))
+            .unwrap();
+        let mut output = ParquetAdapter::<User>::new(path, options).unwrap();
+
+        let stats = StreamingPipeline::extract_streaming("./src/data/data_4.csv", 100).unwrap()
+            .transform(generate_user)
+            .load(|users| output.write(users))
+            .unwrap();
+        output.finalize().unwrap();
+
+        let reader = ParquetReader::new(path, 250).unwrap();
+        assert_eq!(reader.headers(), &vec!["username", "identifier", "first_name", "last_name"]);
+
+        let mut pipeline = StreamingPipeline::from_input(reader);
+        let first = pipeline.chunks.next().unwrap();
+        assert_eq!(first.len(), 250);
+        assert_eq!(&first[0][0], "kgath0");
+
+        let total = first.len() + pipeline.chunks.map(|chunk| chunk.len()).sum::<usize>();
+        assert_eq!(total, stats.total_filtered);
+    }
+}
@@ -0,0 +1,197 @@
+use std::sync::mpsc::{sync_channel, Receiver};
+use std::thread;
+use rusqlite::types::ValueRef;
+use crate::models::error::{EtlError, EtlResult};
+use crate::models::input::InputPort;
+
+pub const DEFAULT_TABLE: &str = "users";
+
+// Un chunk lu (ou l'erreur bloquante) et les erreurs de lignes rencontrées
+type ChunkMessage = EtlResult<(Vec<csv::StringRecord>, Vec<String>)>;
+
+pub struct SqliteReader {
+    receiver: Receiver<ChunkMessage>,
+    errors: Vec<String>,
+    finished: bool,
+}
+
+impl SqliteReader {
+    pub fn from_table(path: &str, table: &str, chunk_size: usize) -> EtlResult<Self> {
+        let query = format!("SELECT * FROM \"{}\"", table.replace('"', "\"\""));
+        SqliteReader::from_query(path, &query, chunk_size)
+    }
+
+    pub fn from_query(path: &str, query: &str, chunk_size: usize) -> EtlResult<Self> {
+        let conn = rusqlite::Connection::open_with_flags(path, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .map_err(|err| EtlError::sqlite(path, err))?;
+
+        // Valide la requête tout de suite pour remonter l'erreur à l'ouverture
+        conn.prepare(query).map_err(|err| EtlError::sqlite(path, err))?;
+
+        // Un `Statement` emprunte sa connexion : la lecture tourne dans un thread
+        // dédié qui envoie les chunks au fil de l'eau. Le canal borné évite de
+        // lire toute la table d'avance.
+        let (sender, receiver) = sync_channel::<ChunkMessage>(1);
+        let path = path.to_string();
+        let query = query.to_string();
+        let chunk_size = chunk_size.max(1);
+
+        thread::spawn(move || {
+            let result = read_rows(&conn, &query, chunk_size, |chunk, errors| {
+                sender.send(Ok((chunk, errors))).is_ok()
+            });
+
+            if let Err(err) = result {
+                let _ = sender.send(Err(EtlError::sqlite(&path, err)));
+            }
+        });
+
+        Ok(SqliteReader {
+            receiver,
+            errors: Vec::new(),
+            finished: false,
+        })
+    }
+}
+
+// Lit les lignes et appelle `emit` pour chaque chunk ; s'arrête si `emit` renvoie false
+fn read_rows<F>(conn: &rusqlite::Connection, query: &str, chunk_size: usize, mut emit: F) -> Result<(), rusqlite::Error>
+where
+    F: FnMut(Vec<csv::StringRecord>, Vec<String>) -> bool
+{
+    let mut stmt = conn.prepare(query)?;
+    let column_count = stmt.column_count();
+    let mut rows = stmt.query([])?;
+
+    let mut chunk = Vec::with_capacity(chunk_size);
+    let mut errors = Vec::new();
+    let mut index = 0;
+
+    while let Some(row) = rows.next()? {
+        let mut record = csv::StringRecord::new();
+        let mut invalid = None;
+
+        for column in 0..column_count {
+            match value_to_string(row.get_ref(column)?) {
+                Ok(value) => record.push_field(&value),
+                Err(reason) => {
+                    invalid = Some(format!("{} record parse error: colonne {}: {}", index, column, reason));
+                    break;
+                }
+            }
+        }
+
+        match invalid {
+            Some(err) => errors.push(err),
+            None => chunk.push(record),
+        }
+        index += 1;
+
+        if chunk.len() >= chunk_size && !emit(std::mem::take(&mut chunk), std::mem::take(&mut errors)) {
+            return Ok(());
+        }
+    }
+
+    if !chunk.is_empty() || !errors.is_empty() {
+        emit(chunk, errors);
+    }
+
+    Ok(())
+}
+
+fn value_to_string(value: ValueRef) -> Result<String, String> {
+    match value {
+        ValueRef::Null => Ok(String::new()),
+        ValueRef::Integer(i) => Ok(i.to_string()),
+        ValueRef::Real(f) => Ok(f.to_string()),
+        ValueRef::Text(bytes) => String::from_utf8(bytes.to_vec())
+            .map_err(|err| format!("texte non UTF-8: {}", err)),
+        ValueRef::Blob(_) => Err("les BLOB ne sont pas supportés".to_string()),
+    }
+}
+
+impl InputPort for SqliteReader {
+    fn read_chunk(&mut self) -> EtlResult<Option<Vec<csv::StringRecord>>> {
+        while !self.finished {
+            match self.receiver.recv() {
+                Ok(Ok((chunk, errors))) => {
+                    self.errors.extend(errors);
+                    if !chunk.is_empty() {
+                        return Ok(Some(chunk));
+                    }
+                }
+                Ok(Err(err)) => {
+                    self.finished = true;
+                    return Err(err);
+                }
+                // Le thread de lecture a terminé
+                Err(_) => self.finished = true,
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn take_errors(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.errors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapter::storage_output::sqlite::SqliteAdapter;
+    use crate::models::output::OutputPort;
+    use crate::models::pipeline::Pipeline;
+    use crate::models::stream_pipeline::StreamingPipeline;
+    use crate::models::user::User;
+    use crate::utils::set_user::generate_user;
+
+    fn fill_db(path: &str, count: usize) {
+        let _ = std::fs::remove_file(path);
+        let mut db = SqliteAdapter::new(path).unwrap();
+        let users: Vec<User> = (0..count)
+            .map(|i| User {
+                username: format!("user{}", i),
+                identifier: format!("id-{}", i),
+                first_name: "jean".to_string(),
+                last_name: "dupont".to_string(),
+            })
+            .collect();
+        db.write(&users).unwrap();
+    }
+
+    #[test]
+    fn test_sqlite_source_to_sqlite() {
+        let source = std::env::temp_dir().join("sqlite_reader_source.db");
+        let target = std::env::temp_dir().join("sqlite_reader_target.db");
+        let source = source.to_str().unwrap();
+        let target = target.to_str().unwrap();
+        fill_db(source, 250);
+        let _ = std::fs::remove_file(target);
+
+        let mut output = SqliteAdapter::new(target).unwrap();
+        let reader = SqliteReader::from_table(source, DEFAULT_TABLE, 100).unwrap();
+
+        let stats = StreamingPipeline::from_input(reader)
+            .transform(generate_user)
+            .load(|users| output.write(users))
+            .unwrap();
+
+        assert_eq!(stats.total_filtered, 250);
+        assert_eq!(output.get_all_users().unwrap().len(), 250);
+
+        let query = "SELECT username, identifier, first_name, last_name FROM users WHERE username LIKE 'user1%'";
+        let pipeline = Pipeline::from_input(SqliteReader::from_query(source, query, 100).unwrap()).unwrap();
+        assert_eq!(pipeline.data.len(), 111);
+    }
+
+    #[test]
+    fn test_sqlite_source_invalid_query() {
+        let source = std::env::temp_dir().join("sqlite_reader_invalid.db");
+        let source = source.to_str().unwrap();
+        fill_db(source, 1);
+
+        assert!(SqliteReader::from_query(source, "SELECT * FROM missing", 10).is_err());
+    }
+}
@@ -0,0 +1,149 @@
+use calamine::{open_workbook_auto, Data, Range, Reader};
+use crate::models::error::{EtlError, EtlResult};
+use crate::models::input::InputPort;
+
+// La feuille est chargée par calamine, les lignes sont ensuite rendues par chunks
+pub struct XlsxReader {
+    range: Range<Data>,
+    headers: csv::StringRecord,
+    chunk_size: usize,
+    next_row: usize,
+}
+
+impl XlsxReader {
+    // `sheet` : nom de la feuille, la première sinon.
+    // `cells` : plage au format `A1:D100` ; la première ligne de la plage est l'en-tête.
+    pub fn new(path: &str, sheet: Option<&str>, cells: Option<&str>, chunk_size: usize) -> EtlResult<Self> {
+        let xlsx_error = |err: calamine::Error| EtlError::Xlsx { path: path.to_string(), source: Box::new(err) };
+
+        let mut workbook = open_workbook_auto(path).map_err(xlsx_error)?;
+
+        let sheet = match sheet {
+            Some(name) => name.to_string(),
+            None => workbook.sheet_names().first().cloned()
+                .ok_or_else(|| EtlError::InvalidSource { path: path.to_string(), reason: "classeur vide".to_string() })?,
+        };
+
+        let mut range = workbook.worksheet_range(&sheet).map_err(xlsx_error)?;
+
+        if let Some(cells) = cells {
+            let (start, end) = parse_cell_range(cells)
+                .ok_or_else(|| EtlError::InvalidRecipe(format!("plage de cellules invalide: {}", cells)))?;
+            range = range.range(start, end);
+        }
+
+        let headers = range.rows().next()
+            .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+            .unwrap_or_default();
+
+        Ok(XlsxReader {
+            range,
+            headers,
+            chunk_size: chunk_size.max(1),
+            next_row: 1,
+        })
+    }
+
+    pub fn headers(&self) -> &csv::StringRecord {
+        &self.headers
+    }
+}
+
+impl InputPort for XlsxReader {
+    fn read_chunk(&mut self) -> EtlResult<Option<Vec<csv::StringRecord>>> {
+        // Accès direct par position : `rows().skip()` reparcourrait toutes les lignes déjà lues
+        let (height, width) = self.range.get_size();
+        let end = (self.next_row + self.chunk_size).min(height);
+
+        let chunk: Vec<csv::StringRecord> = (self.next_row..end)
+            .map(|row| {
+                (0..width)
+                    .map(|column| self.range.get((row, column)).map(|cell| cell.to_string()).unwrap_or_default())
+                    .collect()
+            })
+            .collect();
+
+        self.next_row = end;
+
+        if chunk.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(chunk))
+        }
+    }
+}
+
+// `B2:D10` -> ((1, 1), (9, 3)), en (ligne, colonne) à partir de 0
+fn parse_cell_range(cells: &str) -> Option<((u32, u32), (u32, u32))> {
+    let (start, end) = cells.split_once(':')?;
+    Some((parse_cell(start)?, parse_cell(end)?))
+}
+
+fn parse_cell(cell: &str) -> Option<(u32, u32)> {
+    let cell = cell.trim().to_ascii_uppercase();
+    let split = cell.find(|c: char| c.is_ascii_digit())?;
+    let (letters, digits) = cell.split_at(split);
+
+    if letters.is_empty() || !letters.chars().all(|c| c.is_ascii_uppercase()) {
+        return None;
+    }
+
+    let column = letters.chars()
+        .fold(0u32, |acc, c| acc * 26 + (c as u32 - 'A' as u32 + 1));
+    let row: u32 = digits.parse().ok()?;
+
+    if row == 0 {
+        return None;
+    }
+
+    Some((row - 1, column - 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapter::storage_output::xlsx::XlsxAdapter;
+    use crate::models::output::OutputPort;
+    use crate::models::user::User;
+
+    #[test]
+    fn test_parse_cell_range() {
+        assert_eq!(parse_cell_range("A1:D10"), Some(((0, 0), (9, 3))));
+        assert_eq!(parse_cell_range("aa2:ab3"), Some(((1, 26), (2, 27))));
+        assert_eq!(parse_cell_range("A0:B2"), None);
+    }
+
+    #[test]
+    fn test_xlsx_round_trip_with_sheet_split() {
+        let path = std::env::temp_dir().join("xlsx_round_trip.xlsx");
+        let path = path.to_str().unwrap();
+
+        let users: Vec<User> = (0..25)
+            .map(|i| User {
+                username: format!("user{}", i),
+                identifier: format!("id-{}", i),
+                first_name: "Jean".to_string(),
+                last_name: "Dupont".to_string(),
+            })
+            .collect();
+
+        let mut output = XlsxAdapter::<User>::new(path).with_sheet_name("Users").with_max_rows(11);
+        output.write(&users[..15]).unwrap();
+        output.write(&users[15..]).unwrap();
+        output.finalize().unwrap();
+
+        // 10 lignes de données par feuille : Users, Users (2), Users (3)
+        let mut reader = XlsxReader::new(path, Some("Users (3)"), None, 100).unwrap();
+        assert_eq!(reader.headers(), &vec!["username", "identifier", "first_name", "last_name"]);
+        let chunk = reader.read_chunk().unwrap().unwrap();
+        assert_eq!(chunk.len(), 5);
+        assert_eq!(&chunk[0][0], "user20");
+
+        let mut reader = XlsxReader::new(path, None, Some("A1:B4"), 2).unwrap();
+        assert_eq!(reader.read_chunk().unwrap().unwrap().len(), 2);
+        let last = reader.read_chunk().unwrap().unwrap();
+        assert_eq!(last.len(), 1);
+        assert_eq!(last[0], vec!["user2", "id-2"]);
+        assert!(reader.read_chunk().unwrap().is_none());
+    }
+}
@@ -0,0 +1,273 @@
+use std::io::{BufReader, Read};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use quick_xml::escape::resolve_predefined_entity;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use crate::adapter::storage_input::json::FieldMapping;
+use crate::models::error::{EtlError, EtlResult};
+use crate::models::input::InputPort;
+use crate::utils::compression::open_reader;
+use crate::utils::encoding::EncodingOptions;
+
+// Lit les éléments répétés désignés par `record_path` (ex: `/users/user`) sans
+// charger le document. Les champs sont des chemins relatifs à l'élément record :
+// `login`, `name/first`, `@id` (attribut du record), `address/@city`.
+pub struct XmlReader {
+    path: String,
+    reader: Reader<BufReader<Box<dyn Read + Send>>>,
+    record_path: Vec<String>,
+    chunk_size: usize,
+    fields: Vec<FieldMapping>,
+    // Colonnes déduites du premier record quand aucun mapping n'est fourni
+    columns: Option<Vec<String>>,
+    buffer: Vec<u8>,
+    // Éléments ouverts depuis la racine, avec le texte accumulé de chacun
+    stack: Vec<(String, String)>,
+    // Valeurs du record en cours, par chemin relatif
+    current: Option<Vec<(String, String)>>,
+    finished: bool,
+    replaced_chars: Arc<AtomicUsize>,
+}
+
+impl XmlReader {
+    pub fn new(path: &str, record_path: &str, fields: Vec<FieldMapping>, chunk_size: usize, encoding: &EncodingOptions) -> EtlResult<Self> {
+        let record_path: Vec<String> = record_path.split('/')
+            .filter(|part| !part.is_empty())
+            .map(String::from)
+            .collect();
+        if record_path.is_empty() {
+            return Err(EtlError::InvalidRecipe("`record_path` vide pour la source xml".to_string()));
+        }
+
+        let decoded = encoding.decode(path, open_reader(path)?)?;
+
+        Ok(XmlReader {
+            path: path.to_string(),
+            reader: Reader::from_reader(BufReader::new(decoded.reader)),
+            record_path,
+            chunk_size: chunk_size.max(1),
+            fields,
+            columns: None,
+            buffer: Vec::new(),
+            stack: Vec::new(),
+            current: None,
+            finished: false,
+            replaced_chars: decoded.replaced_chars,
+        })
+    }
+
+    // Noms des colonnes produites, connus après le premier record sans mapping
+    pub fn headers(&self) -> Option<csv::StringRecord> {
+        if !self.fields.is_empty() {
+            return Some(self.fields.iter().map(|f| f.name.as_str()).collect());
+        }
+        self.columns.as_ref().map(|columns| columns.iter().collect())
+    }
+
+    fn invalid(&self, reason: impl ToString) -> EtlError {
+        EtlError::InvalidSource { path: self.path.clone(), reason: reason.to_string() }
+    }
+
+    fn in_record(&self) -> bool {
+        self.current.is_some()
+    }
+
+    // Chemin de l'élément courant relatif au record, "" pour le record lui-même
+    fn relative_path(&self) -> String {
+        self.stack[self.record_path.len()..].iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    fn open(&mut self, element: &BytesStart) -> EtlResult<()> {
+        let name = String::from_utf8_lossy(element.local_name().as_ref()).into_owned();
+        self.stack.push((name, String::new()));
+
+        let at_record = self.stack.len() == self.record_path.len()
+            && self.stack.iter().zip(&self.record_path).all(|((name, _), expected)| name == expected);
+        if at_record {
+            self.current = Some(Vec::new());
+        }
+
+        if self.in_record() {
+            let prefix = self.relative_path();
+            for attribute in element.attributes() {
+                let attribute = attribute.map_err(|err| self.invalid(err))?;
+                let value = attribute.decode_and_unescape_value(self.reader.decoder())
+                    .map_err(|err| self.invalid(err))?;
+                let key = String::from_utf8_lossy(attribute.key.local_name().as_ref()).into_owned();
+                let field = if prefix.is_empty() { format!("@{}", key) } else { format!("{}/@{}", prefix, key) };
+
+                if let Some(current) = self.current.as_mut() {
+                    current.push((field, value.into_owned()));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Ferme l'élément courant ; renvoie le record s'il s'agissait de l'élément record
+    fn close(&mut self) -> Option<csv::StringRecord> {
+        if !self.in_record() {
+            self.stack.pop();
+            return None;
+        }
+
+        let relative = self.relative_path();
+        let (_, text) = self.stack.pop()?;
+        let text = text.trim();
+
+        if !relative.is_empty() {
+            if !text.is_empty() {
+                self.current.as_mut()?.push((relative, text.to_string()));
+            }
+            return None;
+        }
+
+        let values = self.current.take()?;
+        Some(self.make_record(values))
+    }
+
+    fn push_text(&mut self, text: &str) {
+        if let Some((_, buffer)) = self.stack.last_mut() {
+            buffer.push_str(text);
+        }
+    }
+
+    // Un chemin répété garde sa première valeur, un chemin absent donne ""
+    fn make_record(&mut self, values: Vec<(String, String)>) -> csv::StringRecord {
+        let lookup = |key: &str| values.iter()
+            .find(|(path, _)| path == key)
+            .map_or("", |(_, value)| value.as_str());
+
+        if !self.fields.is_empty() {
+            return self.fields.iter().map(|field| lookup(&field.pointer)).collect();
+        }
+
+        let columns = self.columns.get_or_insert_with(|| {
+            let mut columns: Vec<String> = Vec::new();
+            for (path, _) in &values {
+                if !columns.contains(path) {
+                    columns.push(path.clone());
+                }
+            }
+            columns
+        });
+        columns.iter().map(|column| lookup(column)).collect()
+    }
+}
+
+impl InputPort for XmlReader {
+    fn read_chunk(&mut self) -> EtlResult<Option<Vec<csv::StringRecord>>> {
+        let mut chunk = Vec::new();
+
+        while !self.finished && chunk.len() < self.chunk_size {
+            self.buffer.clear();
+            let event = self.reader.read_event_into(&mut self.buffer)
+                .map_err(|err| EtlError::InvalidSource {
+                    path: self.path.clone(),
+                    reason: format!("XML invalide à l'octet {}: {}", self.reader.error_position(), err),
+                })?
+                .into_owned();
+
+            match event {
+                Event::Start(element) => self.open(&element)?,
+                Event::Empty(element) => {
+                    self.open(&element)?;
+                    if let Some(record) = self.close() {
+                        chunk.push(record);
+                    }
+                },
+                Event::End(_) => {
+                    if let Some(record) = self.close() {
+                        chunk.push(record);
+                    }
+                },
+                Event::Text(text) if self.in_record() => {
+                    let text = text.xml10_content().map_err(|err| self.invalid(err))?;
+                    self.push_text(&text);
+                },
+                Event::CData(data) if self.in_record() => {
+                    let data = data.xml10_content().map_err(|err| self.invalid(err))?;
+                    self.push_text(&data);
+                },
+                Event::GeneralRef(reference) if self.in_record() => {
+                    let text = match reference.resolve_char_ref().map_err(|err| self.invalid(err))? {
+                        Some(c) => c.to_string(),
+                        None => {
+                            let name = reference.decode().map_err(|err| self.invalid(err))?;
+                            resolve_predefined_entity(&name)
+                                .map_or_else(|| format!("&{};", name), String::from)
+                        },
+                    };
+                    self.push_text(&text);
+                },
+                Event::Eof => self.finished = true,
+                _ => {},
+            }
+        }
+
+        if chunk.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(chunk))
+        }
+    }
+
+    fn take_replaced_chars(&mut self) -> usize {
+        self.replaced_chars.swap(0, Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_tmp(name: &str, content: &str) -> String {
+        let path = std::env::temp_dir().join(name);
+        std::fs::write(&path, content).unwrap();
+        path.to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_xml_record_path_with_mapping() {
+        let path = write_tmp("xml_reader_users.xml", r#"<?xml version="1.0"?>
+<export>
+    <users>
+        <user id="1"><login>jdupont</login><name first="Jean">Dupont &amp; Fils</name></user>
+        <user id="2"><login><![CDATA[mmartin]]></login><name first="Marie">Martin</name></user>
+        <user id="3"><name first="Paul"/></user>
+    </users>
+    <user id="ignored"/>
+</export>"#);
+
+        let fields = vec![
+            FieldMapping { name: "id".to_string(), pointer: "@id".to_string() },
+            FieldMapping { name: "login".to_string(), pointer: "login".to_string() },
+            FieldMapping { name: "first".to_string(), pointer: "name/@first".to_string() },
+            FieldMapping { name: "last".to_string(), pointer: "name".to_string() },
+        ];
+        let mut reader = XmlReader::new(&path, "/export/users/user", fields, 2, &EncodingOptions::default()).unwrap();
+
+        let first = reader.read_chunk().unwrap().unwrap();
+        assert_eq!(first[0], vec!["1", "jdupont", "Jean", "Dupont & Fils"]);
+        assert_eq!(first[1], vec!["2", "mmartin", "Marie", "Martin"]);
+
+        let last = reader.read_chunk().unwrap().unwrap();
+        assert_eq!(last, vec![csv::StringRecord::from(vec!["3", "", "Paul", ""])]);
+        assert!(reader.read_chunk().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_xml_columns_from_first_record() {
+        let path = write_tmp("xml_reader_auto.xml", "<users><user id=\"1\"><login>a</login></user><user><login>b</login></user></users>");
+
+        let mut reader = XmlReader::new(&path, "users/user", Vec::new(), 10, &EncodingOptions::default()).unwrap();
+        let chunk = reader.read_chunk().unwrap().unwrap();
+
+        assert_eq!(reader.headers().unwrap(), vec!["@id", "login"]);
+        assert_eq!(chunk[1], vec!["", "b"]);
+    }
+}
@@ -0,0 +1,112 @@
+use std::marker::PhantomData;
+use crate::models::error::{EtlError, EtlResult};
+use crate::models::output::OutputPort;
+use crate::models::record::Record;
+use crate::utils::compression::{Compression, OutputWriter};
+
+pub struct CsvAdapter<T> {
+    path: String,
+    writer: Option<csv::Writer<OutputWriter>>,
+    columns: Vec<String>,
+    header_written: bool,
+    _record: PhantomData<fn(&T)>,
+}
+
+impl<T: Record> CsvAdapter<T> {
+    pub fn new(path: &str, compression: Compression, level: Option<i32>) -> EtlResult<Self> {
+        let output = OutputWriter::create(path, compression, level)?;
+        let columns = T::field_names().into_iter().map(String::from).collect();
+
+        Ok(CsvAdapter {
+            path: path.to_string(),
+            writer: Some(csv::Writer::from_writer(output)),
+            columns,
+            header_written: false,
+            _record: PhantomData,
+        })
+    }
+
+    pub fn with_columns(mut self, columns: Vec<String>) -> EtlResult<Self> {
+        if columns.len() != self.columns.len() {
+            return Err(EtlError::InvalidRecipe(format!(
+                "{} colonnes attendues pour la sortie csv, {} fournies",
+                self.columns.len(), columns.len()
+            )));
+        }
+        self.columns = columns;
+        Ok(self)
+    }
+
+    fn writer(&mut self) -> EtlResult<&mut csv::Writer<OutputWriter>> {
+        self.writer.as_mut()
+            .ok_or_else(|| EtlError::InvalidRecipe(format!("{} est déjà finalisé", self.path)))
+    }
+
+    // L'en-tête est écrit au premier chunk, ou à la finalisation si aucun record
+    fn write_header(&mut self) -> EtlResult<()> {
+        if self.header_written {
+            return Ok(());
+        }
+        let columns = self.columns.clone();
+        let path = self.path.clone();
+        self.writer()?.write_record(&columns).map_err(|err| EtlError::csv(&path, err))?;
+        self.header_written = true;
+        Ok(())
+    }
+}
+
+impl<T: Record> OutputPort<T> for CsvAdapter<T> {
+    fn write(&mut self, data: &[T]) -> EtlResult<()> {
+        self.write_header()?;
+        let path = self.path.clone();
+        let writer = self.writer()?;
+
+        for record in data {
+            writer.write_record(record.field_values()).map_err(|err| EtlError::csv(&path, err))?;
+        }
+        Ok(())
+    }
+
+    // Vide le buffer csv puis termine le flux compressé
+    fn finalize(&mut self) -> EtlResult<()> {
+        if self.writer.is_none() {
+            return Ok(());
+        }
+        self.write_header()?;
+
+        if let Some(writer) = self.writer.take() {
+            let output = writer.into_inner()
+                .map_err(|err| EtlError::io(&self.path, err.into_error()))?;
+            output.finish().map_err(|err| EtlError::io(&self.path, err))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "compression"))]
+mod tests {
+    use super::*;
+    use crate::models::stream_pipeline::StreamingPipeline;
+    use crate::models::user::User;
+    use crate::utils::set_user::generate_user;
+
+    #[test]
+    fn test_gzip_csv_round_trip() {
+        let path = std::env::temp_dir().join("csv_output_round_trip.csv.gz");
+        let path = path.to_str().unwrap();
+
+        let mut output = CsvAdapter::<User>::new(path, Compression::from_extension(path), Some(6)).unwrap();
+        let stats = StreamingPipeline::extract_streaming("./src/data/data_4.csv", 100).unwrap()
+            .transform(generate_user)
+            .load(|users| output.write(users))
+            .unwrap();
+        output.finalize().unwrap();
+
+        // Relu via le même chemin que les sources : décompression transparente
+        let total: usize = StreamingPipeline::extract_streaming(path, 100).unwrap()
+            .chunks
+            .map(|chunk| chunk.len())
+            .sum();
+        assert_eq!(total, stats.total_filtered);
+    }
+}
@@ -0,0 +1,121 @@
+use std::io::Write;
+use std::marker::PhantomData;
+use serde_json::{Map, Value};
+use crate::adapter::storage_input::json::JsonLayout;
+use crate::models::error::{EtlError, EtlResult};
+use crate::models::output::OutputPort;
+use crate::models::record::Record;
+use crate::utils::compression::{Compression, OutputWriter};
+
+// Écrit un objet par record, clés dans l'ordre des colonnes
+pub struct JsonAdapter<T> {
+    path: String,
+    writer: Option<OutputWriter>,
+    layout: JsonLayout,
+    columns: Vec<String>,
+    written: usize,
+    _record: PhantomData<fn(&T)>,
+}
+
+impl<T: Record> JsonAdapter<T> {
+    pub fn new(path: &str, layout: JsonLayout, compression: Compression, level: Option<i32>) -> EtlResult<Self> {
+        let columns = T::field_names().into_iter().map(String::from).collect();
+
+        Ok(JsonAdapter {
+            path: path.to_string(),
+            writer: Some(OutputWriter::create(path, compression, level)?),
+            layout,
+            columns,
+            written: 0,
+            _record: PhantomData,
+        })
+    }
+
+    pub fn with_columns(mut self, columns: Vec<String>) -> EtlResult<Self> {
+        if columns.len() != self.columns.len() {
+            return Err(EtlError::InvalidRecipe(format!(
+                "{} colonnes attendues pour la sortie json, {} fournies",
+                self.columns.len(), columns.len()
+            )));
+        }
+        self.columns = columns;
+        Ok(self)
+    }
+}
+
+impl<T: Record> OutputPort<T> for JsonAdapter<T> {
+    fn write(&mut self, data: &[T]) -> EtlResult<()> {
+        let writer = self.writer.as_mut()
+            .ok_or_else(|| EtlError::InvalidRecipe(format!("{} est déjà finalisé", self.path)))?;
+        let path = self.path.as_str();
+
+        for record in data {
+            let object: Map<String, Value> = self.columns.iter().cloned()
+                .zip(record.field_values().into_iter().map(|value| Value::String(value.to_string())))
+                .collect();
+
+            let separator: &[u8] = match (self.layout, self.written) {
+                (JsonLayout::Array, 0) => b"[\n",
+                (JsonLayout::Array, _) => b",\n",
+                (JsonLayout::Lines, _) => b"",
+            };
+            writer.write_all(separator).map_err(|err| EtlError::io(path, err))?;
+            serde_json::to_writer(&mut *writer, &object)
+                .map_err(|err| EtlError::Json { path: path.to_string(), source: err })?;
+            if self.layout == JsonLayout::Lines {
+                writer.write_all(b"\n").map_err(|err| EtlError::io(path, err))?;
+            }
+
+            self.written += 1;
+        }
+        Ok(())
+    }
+
+    // Ferme le tableau JSON puis termine le flux compressé
+    fn finalize(&mut self) -> EtlResult<()> {
+        if let Some(mut writer) = self.writer.take() {
+            let end: &[u8] = match (self.layout, self.written) {
+                (JsonLayout::Array, 0) => b"[]\n",
+                (JsonLayout::Array, _) => b"\n]\n",
+                (JsonLayout::Lines, _) => b"",
+            };
+            writer.write_all(end).map_err(|err| EtlError::io(&self.path, err))?;
+            writer.finish().map_err(|err| EtlError::io(&self.path, err))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapter::storage_input::json::JsonReader;
+    use crate::models::input::InputPort;
+    use crate::models::user::User;
+
+    #[test]
+    fn test_json_array_round_trip() {
+        let path = std::env::temp_dir().join("json_output_round_trip.json");
+        let path = path.to_str().unwrap();
+
+        let users: Vec<User> = (0..3)
+            .map(|i| User {
+                username: format!("user{}", i),
+                identifier: format!("id-{}", i),
+                first_name: "Jean".to_string(),
+                last_name: "Dupont".to_string(),
+            })
+            .collect();
+
+        let mut output = JsonAdapter::<User>::new(path, JsonLayout::Array, Compression::None, None).unwrap();
+        output.write(&users[..2]).unwrap();
+        output.write(&users[2..]).unwrap();
+        output.finalize().unwrap();
+
+        let mut reader = JsonReader::new(path, JsonLayout::Array, Vec::new(), 10).unwrap();
+        let chunk = reader.read_chunk().unwrap().unwrap();
+        assert_eq!(chunk.len(), 3);
+        assert_eq!(reader.headers().unwrap(), vec!["username", "identifier", "first_name", "last_name"]);
+        assert_eq!(chunk[2], vec!["user2", "id-2", "Jean", "Dupont"]);
+    }
+}
@@ -0,0 +1,128 @@
+use std::fs::File;
+use std::marker::PhantomData;
+use std::sync::Arc;
+use arrow_array::{ArrayRef, RecordBatch, StringArray};
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use parquet::arrow::ArrowWriter;
+use parquet::basic::{Compression, GzipLevel, ZstdLevel};
+use parquet::file::properties::WriterProperties;
+use crate::models::error::{EtlError, EtlResult};
+use crate::models::input::DEFAULT_CHUNK_SIZE;
+use crate::models::output::OutputPort;
+use crate::models::record::Record;
+
+#[derive(Debug, Clone)]
+pub struct ParquetOptions {
+    // Taille max d'un row group ; chaque `write` ferme aussi son row group,
+    // un chunk du pipeline donne donc au plus un row group.
+    pub row_group_size: usize,
+    pub compression: Compression,
+}
+
+impl Default for ParquetOptions {
+    fn default() -> Self {
+        ParquetOptions {
+            row_group_size: DEFAULT_CHUNK_SIZE,
+            compression: Compression::SNAPPY,
+        }
+    }
+}
+
+impl ParquetOptions {
+    // `name` : none, snappy, gzip ou zstd ; `level` pour gzip/zstd
+    pub fn with_compression(mut self, name: &str, level: Option<i32>) -> EtlResult<Self> {
+        let invalid = |reason: String| EtlError::InvalidRecipe(format!("compression parquet: {}", reason));
+
+        self.compression = match name {
+            "none" => Compression::UNCOMPRESSED,
+            "snappy" => Compression::SNAPPY,
+            "gzip" => Compression::GZIP(match level {
+                Some(level) => GzipLevel::try_new(level as u32).map_err(|err| invalid(err.to_string()))?,
+                None => GzipLevel::default(),
+            }),
+            "zstd" => Compression::ZSTD(match level {
+                Some(level) => ZstdLevel::try_new(level).map_err(|err| invalid(err.to_string()))?,
+                None => ZstdLevel::default(),
+            }),
+            other => return Err(invalid(format!("codec inconnu `{}`", other))),
+        };
+
+        Ok(self)
+    }
+}
+
+pub struct ParquetAdapter<T> {
+    path: String,
+    schema: SchemaRef,
+    writer: Option<ArrowWriter<File>>,
+    _record: PhantomData<fn(&T)>,
+}
+
+impl<T: Record> ParquetAdapter<T> {
+    // Schéma déduit du type de record
+    pub fn new(path: &str, options: ParquetOptions) -> EtlResult<Self> {
+        let columns = T::field_names().into_iter().map(String::from).collect();
+        ParquetAdapter::with_columns(path, columns, options)
+    }
+
+    // Schéma imposé (ex: renommage des colonnes dans la recette)
+    pub fn with_columns(path: &str, columns: Vec<String>, options: ParquetOptions) -> EtlResult<Self> {
+        if columns.len() != T::field_names().len() {
+            return Err(EtlError::InvalidRecipe(format!(
+                "{} colonnes attendues pour la sortie parquet, {} fournies",
+                T::field_names().len(), columns.len()
+            )));
+        }
+
+        let fields: Vec<Field> = columns.iter()
+            .map(|name| Field::new(name, DataType::Utf8, false))
+            .collect();
+        let schema = Arc::new(Schema::new(fields));
+
+        let properties = WriterProperties::builder()
+            .set_max_row_group_size(options.row_group_size.max(1))
+            .set_compression(options.compression)
+            .build();
+
+        let file = File::create(path).map_err(|err| EtlError::io(path, err))?;
+        let writer = ArrowWriter::try_new(file, schema.clone(), Some(properties))
+            .map_err(|err| EtlError::parquet(path, err))?;
+
+        Ok(ParquetAdapter {
+            path: path.to_string(),
+            schema,
+            writer: Some(writer),
+            _record: PhantomData,
+        })
+    }
+}
+
+impl<T: Record> OutputPort<T> for ParquetAdapter<T> {
+    fn write(&mut self, data: &[T]) -> EtlResult<()> {
+        if data.is_empty() {
+            return Ok(());
+        }
+
+        let rows: Vec<Vec<&str>> = data.iter().map(|record| record.field_values()).collect();
+        let columns: Vec<ArrayRef> = (0..self.schema.fields().len())
+            .map(|col| Arc::new(StringArray::from_iter_values(rows.iter().map(|row| row[col]))) as ArrayRef)
+            .collect();
+
+        let batch = RecordBatch::try_new(self.schema.clone(), columns)
+            .map_err(|err| EtlError::parquet(&self.path, err.into()))?;
+
+        let writer = self.writer.as_mut()
+            .ok_or_else(|| EtlError::InvalidRecipe(format!("{} est déjà finalisé", self.path)))?;
+
+        writer.write(&batch).map_err(|err| EtlError::parquet(&self.path, err))?;
+        writer.flush().map_err(|err| EtlError::parquet(&self.path, err))
+    }
+
+    // Écrit le footer : sans lui le fichier n'est pas lisible
+    fn finalize(&mut self) -> EtlResult<()> {
+        if let Some(writer) = self.writer.take() {
+            writer.close().map_err(|err| EtlError::parquet(&self.path, err))?;
+        }
+        Ok(())
+    }
+}
@@ -1,37 +1,45 @@
-use std::error::Error;
+use crate::models::error::{EtlError, EtlResult};
 use crate::models::output::OutputPort;
-use crate::User;
+use crate::models::user::User;
 
 pub struct SqliteAdapter {
     db: Database,
 }
 
 impl SqliteAdapter {
-    pub fn new(path: &str) -> Result<Self, Box<dyn Error >> {
-        let db = Database::new(path);
-        db.init()?;
+    pub fn new(path: &str) -> EtlResult<Self> {
+        let db = Database::new(path)
+            .map_err(|err| EtlError::sqlite(path, err))?;
+        db.init().map_err(|err| EtlError::sqlite(path, err))?;
         Ok(SqliteAdapter { db })
     }
+
+    pub fn get_all_users(&self) -> EtlResult<Vec<User>> {
+        self.db.get_all_users()
+            .map_err(|err| EtlError::sqlite(&self.db.path, err))
+    }
 }
 
 impl OutputPort<User> for SqliteAdapter {
-    fn write(&mut self, data: &[User]) -> Result<(), Box<dyn Error>> {
-        self.db.insert_user(data)?;
-        Ok(())
+    fn write(&mut self, data: &[User]) -> EtlResult<()> {
+        self.db.insert_user(data)
+            .map_err(|err| EtlError::sqlite(&self.db.path, err))
     }
 }
 
 
 
 struct Database {
+    path: String,
     conn: rusqlite::Connection,
 }
 
 impl Database {
-    fn new(path: &str) -> Self {
-        Database {
-            conn: rusqlite::Connection::open(path).unwrap(),
-        }
+    fn new(path: &str) -> Result<Self, rusqlite::Error> {
+        Ok(Database {
+            path: path.to_string(),
+            conn: rusqlite::Connection::open(path)?,
+        })
     }
 
     fn init(&self) -> Result<(), rusqlite::Error> {
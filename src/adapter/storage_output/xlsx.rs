@@ -0,0 +1,130 @@
+use std::marker::PhantomData;
+use rust_xlsxwriter::{Format, Workbook, Worksheet};
+use crate::models::error::{EtlError, EtlResult};
+use crate::models::output::OutputPort;
+use crate::models::record::Record;
+
+// Limite Excel : 1 048 576 lignes par feuille, en-tête compris
+pub const MAX_SHEET_ROWS: u32 = 1_048_576;
+pub const DEFAULT_SHEET_NAME: &str = "Data";
+
+pub struct XlsxAdapter<T> {
+    path: String,
+    workbook: Workbook,
+    columns: Vec<String>,
+    sheet_name: String,
+    max_rows: u32,
+    sheet_count: usize,
+    // Prochaine ligne à écrire dans la feuille courante, 0 = aucune feuille ouverte
+    next_row: u32,
+    header_format: Format,
+    _record: PhantomData<fn(&T)>,
+}
+
+impl<T: Record> XlsxAdapter<T> {
+    pub fn new(path: &str) -> Self {
+        let columns = T::field_names().into_iter().map(String::from).collect();
+        XlsxAdapter {
+            path: path.to_string(),
+            workbook: Workbook::new(),
+            columns,
+            sheet_name: DEFAULT_SHEET_NAME.to_string(),
+            max_rows: MAX_SHEET_ROWS,
+            sheet_count: 0,
+            next_row: 0,
+            header_format: Format::new().set_bold(),
+            _record: PhantomData,
+        }
+    }
+
+    pub fn with_columns(mut self, columns: Vec<String>) -> EtlResult<Self> {
+        if columns.len() != self.columns.len() {
+            return Err(EtlError::InvalidRecipe(format!(
+                "{} colonnes attendues pour la sortie xlsx, {} fournies",
+                self.columns.len(), columns.len()
+            )));
+        }
+        self.columns = columns;
+        Ok(self)
+    }
+
+    pub fn with_sheet_name(mut self, name: &str) -> Self {
+        self.sheet_name = name.to_string();
+        self
+    }
+
+    // Nombre max de lignes par feuille (en-tête compris) avant d'en ouvrir une nouvelle
+    pub fn with_max_rows(mut self, max_rows: u32) -> Self {
+        self.max_rows = max_rows.clamp(2, MAX_SHEET_ROWS);
+        self
+    }
+
+    fn current_sheet(&mut self) -> EtlResult<&mut Worksheet> {
+        let index = self.sheet_count - 1;
+        self.workbook.worksheet_from_index(index)
+            .map_err(|err| xlsx_error(&self.path, err))
+    }
+
+    fn open_sheet(&mut self) -> EtlResult<()> {
+        self.sheet_count += 1;
+        let name = if self.sheet_count == 1 {
+            self.sheet_name.clone()
+        } else {
+            format!("{} ({})", self.sheet_name, self.sheet_count)
+        };
+
+        let sheet = self.workbook.add_worksheet();
+        sheet.set_name(&name).map_err(|err| xlsx_error(&self.path, err))?;
+        sheet.write_row_with_format(0, 0, &self.columns, &self.header_format)
+            .map_err(|err| xlsx_error(&self.path, err))?;
+
+        self.next_row = 1;
+        Ok(())
+    }
+}
+
+impl<T: Record> OutputPort<T> for XlsxAdapter<T> {
+    fn write(&mut self, data: &[T]) -> EtlResult<()> {
+        let mut remaining = data;
+
+        while !remaining.is_empty() {
+            if self.sheet_count == 0 || self.next_row >= self.max_rows {
+                self.open_sheet()?;
+            }
+
+            let room = (self.max_rows - self.next_row) as usize;
+            let (batch, rest) = remaining.split_at(room.min(remaining.len()));
+            let mut row = self.next_row;
+            let path = self.path.clone();
+
+            let sheet = self.current_sheet()?;
+            for record in batch {
+                sheet.write_row(row, 0, record.field_values())
+                    .map_err(|err| xlsx_error(&path, err))?;
+                row += 1;
+            }
+
+            self.next_row = row;
+            remaining = rest;
+        }
+
+        Ok(())
+    }
+
+    // Le classeur est construit en mémoire et écrit en une fois ici
+    fn finalize(&mut self) -> EtlResult<()> {
+        if self.sheet_count == 0 {
+            self.open_sheet()?;
+        }
+
+        for sheet in self.workbook.worksheets_mut() {
+            sheet.autofit();
+        }
+
+        self.workbook.save(&self.path).map_err(|err| xlsx_error(&self.path, err))
+    }
+}
+
+fn xlsx_error(path: &str, err: rust_xlsxwriter::XlsxError) -> EtlError {
+    EtlError::Xlsx { path: path.to_string(), source: Box::new(err) }
+}
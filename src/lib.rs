@@ -0,0 +1,11 @@
+pub mod adapter;
+pub mod models;
+pub mod utils;
+
+#[cfg(feature = "sqlite")]
+pub use adapter::storage_output::sqlite::SqliteAdapter;
+pub use models::output::OutputPort;
+pub use models::pipeline::{Pipeline, PipelineStats};
+pub use models::recipe_config::RecipeConfig;
+pub use models::stream_pipeline::StreamingPipeline;
+pub use models::user::User;
@@ -0,0 +1,182 @@
+use std::io::Read;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use crate::models::csv_dialect::CsvDialect;
+use crate::models::error::{EtlError, EtlResult};
+use crate::models::input::InputPort;
+use crate::utils::compression::open_reader;
+use crate::utils::encoding::EncodingOptions;
+
+// Variante de `CsvReader` sur des `csv::ByteRecord` : pas de validation UTF-8
+// à la lecture, seulement les colonnes demandées sont copiées, et les records
+// d'un chunk passé à `read_byte_chunk` sont réutilisés d'un appel à l'autre.
+pub struct CsvByteReader {
+    path: String,
+    reader: csv::Reader<Box<dyn Read + Send>>,
+    chunk_size: usize,
+    // Indices des colonnes gardées, dans l'ordre demandé ; toutes si `None`
+    projection: Option<Vec<usize>>,
+    scratch: csv::ByteRecord,
+    // Numéro de ligne de chaque record du dernier chunk lu, pour les erreurs
+    positions: Vec<usize>,
+    index: usize,
+    errors: Vec<String>,
+    replaced_chars: Arc<AtomicUsize>,
+}
+
+impl CsvByteReader {
+    pub fn new(path: &str, chunk_size: usize) -> EtlResult<Self> {
+        CsvByteReader::with_options(path, chunk_size, &CsvDialect::default(), &EncodingOptions::default())
+    }
+
+    pub fn with_options(path: &str, chunk_size: usize, dialect: &CsvDialect, encoding: &EncodingOptions) -> EtlResult<Self> {
+        let decoded = encoding.decode(path, open_reader(path)?)?;
+        let reader = dialect.reader(path, decoded.reader)?;
+        Ok(CsvByteReader {
+            path: path.to_string(),
+            reader,
+            chunk_size: chunk_size.max(1),
+            projection: None,
+            scratch: csv::ByteRecord::new(),
+            positions: Vec::new(),
+            index: 0,
+            errors: Vec::new(),
+            replaced_chars: decoded.replaced_chars,
+        })
+    }
+
+    // Ne garde que `columns`, désignées par leur nom dans l'en-tête
+    pub fn with_columns(mut self, columns: &[&str]) -> EtlResult<Self> {
+        if !self.reader.has_headers() {
+            return Err(EtlError::InvalidRecipe("projection par nom impossible sans ligne d'en-tête".to_string()));
+        }
+        let headers = self.reader.byte_headers()
+            .map_err(|err| EtlError::csv(&self.path, err))?
+            .clone();
+
+        let projection = columns.iter()
+            .map(|column| {
+                headers.iter()
+                    .position(|header| header == column.as_bytes())
+                    .ok_or_else(|| EtlError::InvalidSource {
+                        path: self.path.clone(),
+                        reason: format!("colonne `{}` absente de l'en-tête", column),
+                    })
+            })
+            .collect::<EtlResult<Vec<usize>>>()?;
+
+        self.projection = Some(projection);
+        Ok(self)
+    }
+
+    // Ne garde que les colonnes d'indices `columns`
+    pub fn with_column_indices(mut self, columns: Vec<usize>) -> Self {
+        self.projection = Some(columns);
+        self
+    }
+
+    /// Remplit `chunk` jusqu'à `chunk_size` records en réutilisant ses buffers ;
+    /// renvoie `false` en fin de source. Un champ projeté absent est laissé vide.
+    pub fn read_byte_chunk(&mut self, chunk: &mut Vec<csv::ByteRecord>) -> EtlResult<bool> {
+        let mut len = 0;
+        self.positions.clear();
+
+        while len < self.chunk_size {
+            if chunk.len() == len {
+                chunk.push(csv::ByteRecord::new());
+            }
+
+            let result = match &self.projection {
+                None => self.reader.read_byte_record(&mut chunk[len]),
+                Some(projection) => {
+                    let result = self.reader.read_byte_record(&mut self.scratch);
+                    if let Ok(true) = result {
+                        let record = &mut chunk[len];
+                        record.clear();
+                        for &column in projection {
+                            record.push_field(self.scratch.get(column).unwrap_or_default());
+                        }
+                    }
+                    result
+                },
+            };
+
+            match result {
+                Ok(true) => {
+                    self.positions.push(self.index);
+                    len += 1;
+                },
+                Ok(false) => break,
+                // Lecture ou décompression impossible : bloquant, comme pour `CsvReader`
+                Err(err) if err.is_io_error() => return Err(EtlError::io(&self.path, err.into())),
+                Err(err) => self.errors.push(format!("{} record parse error: {}", self.index, err)),
+            }
+            self.index += 1;
+        }
+
+        chunk.truncate(len);
+        Ok(len > 0)
+    }
+
+    pub fn take_replaced_chars(&mut self) -> usize {
+        self.replaced_chars.swap(0, Ordering::Relaxed)
+    }
+}
+
+// Pour les pipelines existants : l'UTF-8 n'est validé qu'après projection
+impl InputPort for CsvByteReader {
+    fn read_chunk(&mut self) -> EtlResult<Option<Vec<csv::StringRecord>>> {
+        let mut records = Vec::with_capacity(self.chunk_size);
+        if !self.read_byte_chunk(&mut records)? {
+            return Ok(None);
+        }
+
+        let mut chunk = Vec::with_capacity(records.len());
+        for (record, position) in records.into_iter().zip(&self.positions) {
+            match csv::StringRecord::from_byte_record(record) {
+                Ok(record) => chunk.push(record),
+                Err(err) => self.errors.push(format!("{} record parse error: {}", position, err.utf8_error())),
+            }
+        }
+        Ok(Some(chunk))
+    }
+
+    fn take_errors(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.errors)
+    }
+
+    fn take_replaced_chars(&mut self) -> usize {
+        CsvByteReader::take_replaced_chars(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_projection_reuses_buffers() {
+        let path = std::env::temp_dir().join("csv_byte_reader.csv");
+        std::fs::write(&path, b"login,id,ville\njdupont,1,Paris\nmmartin,2,Lyon\nbad\xFF,3,Nice\n").unwrap();
+        let path = path.to_str().unwrap();
+
+        let mut reader = CsvByteReader::new(path, 2).unwrap()
+            .with_columns(&["ville", "login"]).unwrap();
+
+        let mut chunk = Vec::new();
+        assert!(reader.read_byte_chunk(&mut chunk).unwrap());
+        assert_eq!(chunk[0], vec!["Paris", "jdupont"]);
+        let buffer = chunk[0].as_slice().as_ptr();
+
+        assert!(reader.read_byte_chunk(&mut chunk).unwrap());
+        assert_eq!(chunk.len(), 1);
+        assert_eq!(chunk[0].as_slice().as_ptr(), buffer);
+        assert!(!reader.read_byte_chunk(&mut chunk).unwrap());
+
+        // Via `InputPort`, un champ projeté invalide est une erreur de record
+        let mut reader = CsvByteReader::new(path, 10).unwrap().with_column_indices(vec![2, 0]);
+        let chunk = reader.read_chunk().unwrap().unwrap();
+        assert_eq!(chunk.len(), 2);
+        assert_eq!(reader.take_errors(), ["2 record parse error: invalid utf-8: invalid UTF-8 in field 1 near byte index 3"]);
+    }
+}
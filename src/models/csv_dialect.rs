@@ -0,0 +1,231 @@
+use std::cmp::Reverse;
+use std::io::{Cursor, Read};
+use serde::Deserialize;
+use crate::models::error::{EtlError, EtlResult};
+
+// Taille de l'échantillon lu pour la détection automatique
+pub const SNIFF_BYTES: usize = 64 * 1024;
+const SNIFF_LINES: usize = 50;
+const SNIFF_DELIMITERS: [u8; 4] = [b',', b';', b'\t', b'|'];
+
+// Options de lecture d'une source CSV. `delimiter` et `has_headers` non
+// renseignés sont devinés sur les premières lignes quand `auto_detect` est actif,
+// sinon `,` et `true`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct CsvDialect {
+    #[serde(default)]
+    pub delimiter: Option<char>,
+    #[serde(default = "default_quote")]
+    pub quote: char,
+    #[serde(default)]
+    pub escape: Option<char>,
+    #[serde(default)]
+    pub comment: Option<char>,
+    #[serde(default)]
+    pub has_headers: Option<bool>,
+    // Accepte des lignes au nombre de champs variable
+    #[serde(default)]
+    pub flexible: bool,
+    // Retire les espaces autour des champs
+    #[serde(default)]
+    pub trim: bool,
+    #[serde(default)]
+    pub auto_detect: bool,
+}
+
+fn default_quote() -> char {
+    '"'
+}
+
+impl Default for CsvDialect {
+    fn default() -> Self {
+        CsvDialect {
+            delimiter: None,
+            quote: default_quote(),
+            escape: None,
+            comment: None,
+            has_headers: None,
+            flexible: false,
+            trim: false,
+            auto_detect: false,
+        }
+    }
+}
+
+impl CsvDialect {
+    // Construit le lecteur csv ; en mode auto, l'échantillon lu est rejoué devant le reste du flux
+    pub fn reader(&self, path: &str, input: Box<dyn Read + Send>) -> EtlResult<csv::Reader<Box<dyn Read + Send>>> {
+        let (dialect, input) = if self.auto_detect && (self.delimiter.is_none() || self.has_headers.is_none()) {
+            let mut sample = Vec::new();
+            let mut input = input;
+            (&mut input).take(SNIFF_BYTES as u64).read_to_end(&mut sample)
+                .map_err(|err| EtlError::io(path, err))?;
+
+            let dialect = self.resolved(&sample)?;
+            let replay: Box<dyn Read + Send> = Box::new(Cursor::new(sample).chain(input));
+            (dialect, replay)
+        } else {
+            (self.resolved(&[])?, input)
+        };
+
+        Ok(dialect.builder()?.from_reader(input))
+    }
+
+    // Dialecte avec `delimiter` et `has_headers` renseignés, devinés sur `sample`
+    // en mode auto (les premiers octets de la source)
+    pub fn resolved(&self, sample: &[u8]) -> EtlResult<CsvDialect> {
+        let quote = ascii(self.quote, "quote")?;
+        let delimiter = match self.delimiter {
+            Some(delimiter) => ascii(delimiter, "delimiter")?,
+            None if self.auto_detect => sniff_delimiter(sample, quote),
+            None => b',',
+        };
+        let has_headers = match self.has_headers {
+            Some(has_headers) => has_headers,
+            None if self.auto_detect => sniff_has_headers(sample, delimiter, quote),
+            None => true,
+        };
+
+        Ok(CsvDialect {
+            delimiter: Some(delimiter as char),
+            has_headers: Some(has_headers),
+            auto_detect: false,
+            ..self.clone()
+        })
+    }
+
+    pub fn builder(&self) -> EtlResult<csv::ReaderBuilder> {
+        let mut builder = csv::ReaderBuilder::new();
+        builder
+            .delimiter(ascii(self.delimiter.unwrap_or(','), "delimiter")?)
+            .quote(ascii(self.quote, "quote")?)
+            .escape(self.escape.map(|c| ascii(c, "escape")).transpose()?)
+            .comment(self.comment.map(|c| ascii(c, "comment")).transpose()?)
+            .has_headers(self.has_headers.unwrap_or(true))
+            .flexible(self.flexible)
+            .trim(if self.trim { csv::Trim::All } else { csv::Trim::None });
+
+        Ok(builder)
+    }
+}
+
+fn ascii(c: char, option: &str) -> EtlResult<u8> {
+    if c.is_ascii() {
+        Ok(c as u8)
+    } else {
+        Err(EtlError::InvalidRecipe(format!("`{}` doit être un caractère ASCII, reçu `{}`", option, c)))
+    }
+}
+
+// Lignes complètes de l'échantillon ; la dernière peut être coupée par la limite de taille
+fn sample_lines(sample: &[u8]) -> Vec<&[u8]> {
+    let complete = match sample.iter().rposition(|&b| b == b'\n') {
+        Some(end) if sample.len() == SNIFF_BYTES => &sample[..end],
+        _ => sample,
+    };
+
+    complete.split(|&b| b == b'\n')
+        .map(|line| line.strip_suffix(b"\r").unwrap_or(line))
+        .filter(|line| !line.is_empty())
+        .take(SNIFF_LINES)
+        .collect()
+}
+
+fn count_outside_quotes(line: &[u8], delimiter: u8, quote: u8) -> usize {
+    let mut quoted = false;
+    line.iter()
+        .filter(|&&b| {
+            if b == quote {
+                quoted = !quoted;
+            }
+            b == delimiter && !quoted
+        })
+        .count()
+}
+
+// Garde le séparateur présent avec le même nombre d'occurrences sur le plus de
+// lignes, puis le plus fréquent ; `,` si aucun candidat n'apparaît.
+fn sniff_delimiter(sample: &[u8], quote: u8) -> u8 {
+    let lines = sample_lines(sample);
+
+    // À égalité, le premier candidat de la liste l'emporte
+    SNIFF_DELIMITERS.iter()
+        .map(|&delimiter| {
+            let counts: Vec<usize> = lines.iter()
+                .map(|line| count_outside_quotes(line, delimiter, quote))
+                .collect();
+            let first = counts.first().copied().unwrap_or(0);
+            let consistent = counts.iter().filter(|&&count| count == first && count > 0).count();
+            (delimiter, consistent, first)
+        })
+        .filter(|&(_, consistent, _)| consistent > 0)
+        .min_by_key(|&(_, consistent, first)| (Reverse(consistent), Reverse(first)))
+        .map(|(delimiter, _, _)| delimiter)
+        .unwrap_or(b',')
+}
+
+// Vote par colonne : une colonne numérique (ou de longueur fixe) dont la
+// première ligne ne suit pas le motif indique un en-tête. Sans indice, on
+// suppose un en-tête comme le reste du projet.
+fn sniff_has_headers(sample: &[u8], delimiter: u8, quote: u8) -> bool {
+    let lines = sample_lines(sample).join(&b'\n');
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .quote(quote)
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(lines.as_slice());
+
+    let rows: Vec<csv::StringRecord> = reader.records().filter_map(Result::ok).collect();
+    let Some((first, rest)) = rows.split_first() else {
+        return true;
+    };
+    if rest.is_empty() {
+        return true;
+    }
+
+    let mut votes = 0i32;
+    for (column, header) in first.iter().enumerate() {
+        let values: Vec<&str> = rest.iter().filter_map(|row| row.get(column)).collect();
+        if values.len() != rest.len() {
+            continue;
+        }
+
+        if values.iter().all(|value| value.trim().parse::<f64>().is_ok()) {
+            votes += if header.trim().parse::<f64>().is_ok() { -1 } else { 1 };
+        } else if values.iter().all(|value| value.len() == values[0].len()) {
+            votes += if header.len() == values[0].len() { -1 } else { 1 };
+        }
+    }
+
+    votes >= 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sniff_semicolon_with_header() {
+        let sample = b"nom;ville;montant\n\"Dupont; Jean\";Paris;12,5\nMartin;Lyon;3\n";
+        assert_eq!(sniff_delimiter(sample, b'"'), b';');
+        assert!(sniff_has_headers(sample, b';', b'"'));
+
+        let headerless = b"1\t2020\tParis\n2\t2021\tLyon\n3\t2022\tNice\n";
+        assert_eq!(sniff_delimiter(headerless, b'"'), b'\t');
+        assert!(!sniff_has_headers(headerless, b'\t', b'"'));
+    }
+
+    #[test]
+    fn test_auto_detect_reader_keeps_sampled_rows() {
+        let dialect = CsvDialect { auto_detect: true, trim: true, ..CsvDialect::default() };
+        let input: Box<dyn Read + Send> = Box::new(Cursor::new(b"id;nom\n1; Jean\n2; Marie\n".to_vec()));
+
+        let mut reader = dialect.reader("test", input).unwrap();
+        assert_eq!(reader.headers().unwrap(), vec!["id", "nom"]);
+
+        let rows: Vec<csv::StringRecord> = reader.records().map(Result::unwrap).collect();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[1], vec!["2", "Marie"]);
+    }
+}
@@ -1,44 +1,50 @@
-use std::error::Error;
+use crate::models::csv_dialect::CsvDialect;
 use crate::models::csv_reader::CsvReader;
+use crate::models::csv_split_reader::SplitCsvReader;
+use crate::models::error::EtlResult;
+use crate::models::input::InputPort;
+use crate::models::parallel_input::ParallelOptions;
+use crate::utils::encoding::EncodingOptions;
 
 pub struct MultiCsvReader {
-    readers: Vec<CsvReader>,
-    current_index: usize,
+    input: Box<dyn InputPort>,
 }
 
 impl MultiCsvReader {
-    pub fn new(paths: &[&str], chunk_size: usize) -> Result<Self, Box<dyn Error>> {
-        let readers: Result<Vec<_>, _> = paths.iter()
-            .map(|p| CsvReader::new(p, chunk_size))
-            .collect();
+    pub fn new(paths: &[&str], chunk_size: usize) -> EtlResult<Self> {
+        MultiCsvReader::with_options(paths, chunk_size, &CsvDialect::default(), &EncodingOptions::default(), &ParallelOptions::default())
+    }
+
+    // Le dialecte (et sa détection automatique) et l'encodage s'appliquent à chaque fichier ;
+    // les fichiers sont lus en parallèle selon `parallel`, et découpés si `split_files`
+    // (sauf stdin, fichiers compressés ou transcodés, lus d'un bloc)
+    pub fn with_options(paths: &[&str], chunk_size: usize, dialect: &CsvDialect, encoding: &EncodingOptions, parallel: &ParallelOptions) -> EtlResult<Self> {
+        let readers = paths.iter()
+            .map(|p| {
+                if parallel.split_files && SplitCsvReader::can_split(p, encoding) {
+                    Ok(Box::new(SplitCsvReader::new(p, chunk_size, dialect, parallel.parallelism)?) as Box<dyn InputPort>)
+                } else {
+                    Ok(Box::new(CsvReader::with_options(p, chunk_size, dialect, encoding)?) as Box<dyn InputPort>)
+                }
+            })
+            .collect::<EtlResult<Vec<_>>>()?;
 
         Ok(MultiCsvReader {
-            readers: readers?,
-            current_index: 0
+            input: parallel.chain(readers),
         })
-
     }
 }
 
-impl Iterator for MultiCsvReader {
-    type Item = Vec<csv::StringRecord>;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        loop {
-            if self.current_index >= self.readers.len() {
-                return None;
-            }
+impl InputPort for MultiCsvReader {
+    fn read_chunk(&mut self) -> EtlResult<Option<Vec<csv::StringRecord>>> {
+        self.input.read_chunk()
+    }
 
-            let current_reader = &mut self.readers[self.current_index];
+    fn take_errors(&mut self) -> Vec<String> {
+        self.input.take_errors()
+    }
 
-            match current_reader.next() {
-                Some(record) => {
-                    return Some(record)
-                }
-                None => {
-                    self.current_index += 1;
-                }
-            }
-        }
+    fn take_replaced_chars(&mut self) -> usize {
+        self.input.take_replaced_chars()
     }
-}
\ No newline at end of file
+}
@@ -1,30 +1,49 @@
-use std::fs::File;
+use std::io::Read;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use crate::models::csv_dialect::CsvDialect;
+use crate::models::error::{EtlError, EtlResult};
+use crate::models::input::InputPort;
+use crate::utils::compression::open_reader;
+use crate::utils::encoding::EncodingOptions;
 
 
 pub struct CsvReader {
-    reader: csv::Reader<File>,
+    path: String,
+    reader: csv::Reader<Box<dyn Read + Send>>,
     chunk_size: usize,
     current_record: csv::StringRecord,
+    index: usize,
+    errors: Vec<String>,
+    replaced_chars: Arc<AtomicUsize>,
 }
 
 impl CsvReader {
-    pub fn new(path: &str, chunk_size: usize) -> Result<Self, Box<dyn std::error::Error>> {
-        let reader = csv::Reader::from_path(path)?;
+    pub fn new(path: &str, chunk_size: usize) -> EtlResult<Self> {
+        CsvReader::with_options(path, chunk_size, &CsvDialect::default(), &EncodingOptions::default())
+    }
+
+    // Décompression (.gz, .zst, .bz2), puis transcodage en UTF-8, puis parsing selon le dialecte
+    pub fn with_options(path: &str, chunk_size: usize, dialect: &CsvDialect, encoding: &EncodingOptions) -> EtlResult<Self> {
+        let decoded = encoding.decode(path, open_reader(path)?)?;
+        let reader = dialect.reader(path, decoded.reader)?;
         Ok(CsvReader {
+            path: path.to_string(),
             reader,
             chunk_size,
-            current_record: csv::StringRecord::new()
+            current_record: csv::StringRecord::new(),
+            index: 0,
+            errors: Vec::new(),
+            replaced_chars: decoded.replaced_chars,
         })
     }
 }
 
-impl Iterator for CsvReader {
-    type Item = Vec<csv::StringRecord>;
-
-    fn next(&mut self) -> Option<Self::Item> {
+impl InputPort for CsvReader {
+    fn read_chunk(&mut self) -> EtlResult<Option<Vec<csv::StringRecord>>> {
         let mut chunk = Vec::new();
 
-        for _ in 0..self.chunk_size {
+        while chunk.len() < self.chunk_size {
             // Lire dans le buffer current_record
             match self.reader.read_record(&mut self.current_record) {
                 Ok(true) => {
@@ -35,19 +54,31 @@ impl Iterator for CsvReader {
                     // Fin du fichier
                     break;
                 }
-                Err(_) => {
-                    // Erreur de lecture, on skip
-                    break;
+                Err(err) if err.is_io_error() => {
+                    // Lecture ou décompression impossible : la suite du fichier est perdue
+                    return Err(EtlError::io(&self.path, err.into()));
+                }
+                Err(err) => {
+                    // Ligne invalide : on la note et on passe à la suivante
+                    self.errors.push(format!("{} record parse error: {}", self.index, err));
                 }
             }
+            self.index += 1;
         }
 
         if chunk.is_empty() {
-            None  // Fin de l'iterator
+            Ok(None)
         } else {
-            Some(chunk)
+            Ok(Some(chunk))
         }
+    }
+
+    fn take_errors(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.errors)
+    }
 
+    fn take_replaced_chars(&mut self) -> usize {
+        self.replaced_chars.swap(0, Ordering::Relaxed)
     }
 }
 
@@ -58,10 +89,65 @@ mod tests {
 
     #[test]
     fn test_extract_chunk() {
-        let reader = CsvReader::new("./src/data/data_1.csv", 500).unwrap();
+        let mut reader = CsvReader::new("./src/data/data_1.csv", 500).unwrap();
 
-        for (i, chunk) in reader.enumerate() {
-            println!("Chunk {}: {} records", i, chunk.len());
+        let mut chunks = 0;
+        while let Some(chunk) = reader.read_chunk().unwrap() {
+            assert!(!chunk.is_empty() && chunk.len() <= 500);
+            chunks += 1;
         }
+        assert!(chunks > 0);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_invalid_rows_are_reported() {
+        let path = std::env::temp_dir().join("csv_reader_invalid_rows.csv");
+        std::fs::write(&path, "a,b\n1,2\n3\n4,5\n").unwrap();
+
+        let mut reader = CsvReader::new(path.to_str().unwrap(), 10).unwrap();
+        let chunk = reader.read_chunk().unwrap().unwrap();
+
+        assert_eq!(chunk.len(), 2);
+        assert_eq!(reader.take_errors().len(), 1);
+        assert!(reader.read_chunk().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_io_error_is_fatal() {
+        struct Failing;
+        impl Read for Failing {
+            fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+                Err(std::io::Error::other("corrupt deflate stream"))
+            }
+        }
+
+        let mut reader = CsvReader {
+            path: "broken.csv.gz".to_string(),
+            reader: csv::Reader::from_reader(Box::new(Failing) as Box<dyn Read + Send>),
+            chunk_size: 10,
+            current_record: csv::StringRecord::new(),
+            index: 0,
+            errors: Vec::new(),
+            replaced_chars: Arc::default(),
+        };
+
+        assert!(matches!(reader.read_chunk(), Err(EtlError::Io { .. })));
+        assert!(reader.take_errors().is_empty());
+    }
+
+    #[test]
+    fn test_lossy_encoding_counts_replaced_chars() {
+        use crate::models::stream_pipeline::StreamingPipeline;
+
+        let path = std::env::temp_dir().join("csv_reader_lossy.csv");
+        std::fs::write(&path, b"\xEF\xBB\xBFnom,ville\nH\xE9l\xE8ne,Paris\nJean,Lyon\n").unwrap();
+
+        let encoding = EncodingOptions { encoding: None, lossy_encoding: true };
+        let reader = CsvReader::with_options(path.to_str().unwrap(), 10, &CsvDialect::default(), &encoding).unwrap();
+
+        let stats = StreamingPipeline::from_input(reader).load(|_| Ok(())).unwrap();
+        assert_eq!(stats.total_filtered, 2);
+        assert_eq!(stats.replaced_chars, 2);
+        assert!(stats.errors().is_empty());
+    }
+}
@@ -0,0 +1,312 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use rayon::prelude::*;
+use crate::models::csv_dialect::{CsvDialect, SNIFF_BYTES};
+use crate::models::error::{EtlError, EtlResult};
+use crate::models::input::InputPort;
+use crate::utils::compression::{Compression, STDIO_PATH};
+use crate::utils::encoding::{EncodingOptions, UTF8_BOM};
+
+// En dessous, le découpage coûte plus qu'il ne rapporte
+const MIN_RANGE_BYTES: u64 = 1024 * 1024;
+// Plusieurs plages par thread pour équilibrer la charge
+const RANGES_PER_THREAD: u64 = 4;
+const SCAN_BUFFER: usize = 64 * 1024;
+
+// Plage d'octets (BOM exclu) commençant au début d'un record. `start` est la
+// position qu'aurait le lecteur séquentiel à cet endroit, pour des erreurs identiques.
+#[derive(Debug, Clone)]
+struct ByteRange {
+    start: csv::Position,
+    end: u64,
+    // `index` du premier record de la plage, au sens de `CsvReader`
+    first_index: usize,
+}
+
+// Lit un gros fichier CSV en parallèle : le fichier est découpé en plages
+// alignées sur les records, puis `parallelism` plages sont parsées à la fois.
+// Records et erreurs sont rendus dans l'ordre du fichier, comme `CsvReader`.
+pub struct SplitCsvReader {
+    path: String,
+    dialect: CsvDialect,
+    bom_len: u64,
+    chunk_size: usize,
+    parallelism: usize,
+    ranges: VecDeque<ByteRange>,
+    ready: VecDeque<Vec<csv::StringRecord>>,
+    errors: Vec<String>,
+}
+
+impl SplitCsvReader {
+    // Fichier local non compressé, lu sans transcodage : voir `can_split`
+    pub fn new(path: &str, chunk_size: usize, dialect: &CsvDialect, parallelism: usize) -> EtlResult<Self> {
+        SplitCsvReader::with_range_bytes(path, chunk_size, dialect, parallelism, None)
+    }
+
+    fn with_range_bytes(path: &str, chunk_size: usize, dialect: &CsvDialect, parallelism: usize, range_bytes: Option<u64>) -> EtlResult<Self> {
+        let io_error = |err| EtlError::io(path, err);
+        let mut file = File::open(path).map_err(io_error)?;
+        let len = file.metadata().map_err(io_error)?.len();
+
+        let mut sample = Vec::new();
+        (&mut file).take(SNIFF_BYTES as u64).read_to_end(&mut sample).map_err(io_error)?;
+        let bom_len = if sample.starts_with(UTF8_BOM) { UTF8_BOM.len() } else { 0 };
+        let dialect = dialect.resolved(&sample[bom_len..])?;
+        dialect.builder()?;
+
+        let parallelism = parallelism.max(1);
+        let range_bytes = range_bytes
+            .unwrap_or_else(|| (len / (parallelism as u64 * RANGES_PER_THREAD)).max(MIN_RANGE_BYTES));
+
+        file.seek(SeekFrom::Start(bom_len as u64)).map_err(io_error)?;
+        let ranges = scan_ranges(file, &dialect, range_bytes.max(1)).map_err(io_error)?;
+
+        Ok(SplitCsvReader {
+            path: path.to_string(),
+            dialect,
+            bom_len: bom_len as u64,
+            chunk_size: chunk_size.max(1),
+            parallelism,
+            ranges: ranges.into(),
+            ready: VecDeque::new(),
+            errors: Vec::new(),
+        })
+    }
+
+    // Les plages se calculent sur les octets du fichier : ni stdin, ni
+    // fichier compressé, ni transcodage
+    pub fn can_split(path: &str, encoding: &EncodingOptions) -> bool {
+        if path == STDIO_PATH || !encoding.is_plain_utf8() || Compression::from_extension(path) != Compression::None {
+            return false;
+        }
+
+        let mut head = Vec::new();
+        File::open(path)
+            .and_then(|file| file.take(4).read_to_end(&mut head))
+            .is_ok_and(|_| Compression::from_magic_bytes(&head).is_none())
+    }
+
+    fn parse_range(&self, range: &ByteRange) -> EtlResult<(Vec<csv::StringRecord>, Vec<String>)> {
+        let csv_error = |err| EtlError::csv(&self.path, err);
+        let mut file = File::open(&self.path).map_err(|err| EtlError::io(&self.path, err))?;
+
+        let mut reader = if range.start.byte() == 0 {
+            file.seek(SeekFrom::Start(self.bom_len)).map_err(|err| EtlError::io(&self.path, err))?;
+            self.dialect.builder()?.from_reader(file)
+        } else {
+            // L'en-tête est relu au début du fichier : il fixe le nombre de champs attendu
+            let mut reader = self.dialect.builder()?.from_reader(file);
+            reader.seek_raw(SeekFrom::Start(self.bom_len + range.start.byte()), range.start.clone())
+                .map_err(csv_error)?;
+            reader
+        };
+
+        let mut records = Vec::new();
+        let mut errors = Vec::new();
+        let mut record = csv::StringRecord::new();
+        let mut index = range.first_index;
+
+        // Sinon l'en-tête serait lu avec le premier record, et la fin de plage dépassée
+        if range.start.byte() == 0 && reader.has_headers()
+            && let Err(err) = reader.byte_headers() {
+            if err.is_io_error() {
+                return Err(EtlError::io(&self.path, err.into()));
+            }
+            errors.push(format!("{} record parse error: {}", index, err));
+            index += 1;
+        }
+
+        while reader.position().byte() < range.end {
+            match reader.read_record(&mut record) {
+                Ok(true) => records.push(record.clone()),
+                Ok(false) => break,
+                Err(err) if err.is_io_error() => return Err(EtlError::io(&self.path, err.into())),
+                Err(err) => errors.push(format!("{} record parse error: {}", index, err)),
+            }
+            index += 1;
+        }
+
+        Ok((records, errors))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ScanState {
+    StartRecord,
+    StartField,
+    InField,
+    InQuotedField,
+    InEscapedQuote,
+    // Guillemet lu dans un champ entre guillemets : doublé ou fermant
+    QuoteInQuotedField,
+    InComment,
+}
+
+// Reproduit l'automate de csv-core pour trouver les fins de records sans
+// parser les champs ; coupe à la première fin de record après chaque `range_bytes`.
+fn scan_ranges(mut input: impl Read, dialect: &CsvDialect, range_bytes: u64) -> io::Result<Vec<ByteRange>> {
+    use ScanState::*;
+
+    let delimiter = dialect.delimiter.unwrap_or(',') as u8;
+    let quote = dialect.quote as u8;
+    let escape = dialect.escape.map(|c| c as u8);
+    let comment = dialect.comment.map(|c| c as u8);
+    let header_records = u64::from(dialect.has_headers.unwrap_or(true));
+
+    let field_start = |byte: u8| -> (ScanState, bool) {
+        match byte {
+            b if b == quote => (InQuotedField, false),
+            b if b == delimiter => (StartField, false),
+            b'\r' | b'\n' => (StartRecord, true),
+            _ => (InField, false),
+        }
+    };
+
+    // Octets qui peuvent changer l'état d'un champ ; les autres sont sautés d'un bloc
+    let mut special = [false; 256];
+    for byte in [delimiter, quote, b'\r', b'\n'].into_iter().chain(escape) {
+        special[byte as usize] = true;
+    }
+
+    let mut ranges = Vec::new();
+    let mut start = csv::Position::new();
+    let mut first_index = 0;
+    let mut next_cut = range_bytes;
+
+    let mut state = StartRecord;
+    let mut offset: u64 = 0;
+    let mut line: u64 = 1;
+    let mut records: u64 = 0;
+    let mut buffer = vec![0; SCAN_BUFFER];
+
+    loop {
+        let read = match input.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        };
+
+        let mut bytes = buffer[..read].iter();
+        while let Some(&byte) = bytes.next() {
+            offset += 1;
+            line += u64::from(byte == b'\n');
+
+            let (next, end_of_record) = match state {
+                StartRecord => match byte {
+                    b'\r' | b'\n' => (StartRecord, false),
+                    b if Some(b) == comment => (InComment, false),
+                    b => field_start(b),
+                },
+                StartField => field_start(byte),
+                InField | QuoteInQuotedField if byte == delimiter => (StartField, false),
+                InField | QuoteInQuotedField if byte == b'\r' || byte == b'\n' => (StartRecord, true),
+                InField => (InField, false),
+                QuoteInQuotedField if byte == quote => (InQuotedField, false),
+                QuoteInQuotedField => (InField, false),
+                InQuotedField if byte == quote => (QuoteInQuotedField, false),
+                InQuotedField if Some(byte) == escape => (InEscapedQuote, false),
+                InQuotedField | InEscapedQuote => (InQuotedField, false),
+                InComment if byte == b'\n' => (StartRecord, false),
+                InComment => (InComment, false),
+            };
+            state = next;
+
+            if matches!(state, InField | InQuotedField) {
+                let rest = bytes.as_slice();
+                let plain = rest.iter().position(|b| special[*b as usize]).unwrap_or(rest.len());
+                offset += plain as u64;
+                bytes = rest[plain..].iter();
+            }
+
+            if end_of_record {
+                records += 1;
+                if offset >= next_cut {
+                    ranges.push(ByteRange { start, end: offset, first_index });
+                    start = csv::Position::new();
+                    start.set_byte(offset).set_line(line).set_record(records);
+                    first_index = records.saturating_sub(header_records) as usize;
+                    next_cut = offset + range_bytes;
+                }
+            }
+        }
+    }
+
+    ranges.push(ByteRange { start, end: u64::MAX, first_index });
+    Ok(ranges)
+}
+
+impl InputPort for SplitCsvReader {
+    fn read_chunk(&mut self) -> EtlResult<Option<Vec<csv::StringRecord>>> {
+        if self.ready.is_empty() && !self.ranges.is_empty() {
+            let count = self.parallelism.min(self.ranges.len());
+            let window: Vec<ByteRange> = self.ranges.drain(..count).collect();
+
+            let results: Vec<EtlResult<_>> = window.par_iter()
+                .map(|range| self.parse_range(range))
+                .collect();
+
+            for result in results {
+                let (records, errors) = result?;
+                self.errors.extend(errors);
+
+                let mut records = records.into_iter().peekable();
+                while records.peek().is_some() {
+                    self.ready.push_back(records.by_ref().take(self.chunk_size).collect());
+                }
+            }
+        }
+
+        Ok(self.ready.pop_front())
+    }
+
+    fn take_errors(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.errors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::csv_reader::CsvReader;
+
+    fn read_all(mut port: impl InputPort) -> (Vec<csv::StringRecord>, Vec<String>) {
+        let mut records = Vec::new();
+        let mut errors = Vec::new();
+        while let Some(chunk) = port.read_chunk().unwrap() {
+            records.extend(chunk);
+            errors.extend(port.take_errors());
+        }
+        errors.extend(port.take_errors());
+        (records, errors)
+    }
+
+    #[test]
+    fn test_split_matches_sequential_reader() {
+        let mut content = b"\xEF\xBB\xBFlogin,ville,note\r\n".to_vec();
+        for n in 0..200 {
+            let row = match n % 7 {
+                0 => format!("user{n},\"Paris\r\nCedex {n}\",\"dit \"\"ok\"\"\"\r\n"),
+                1 => format!("user{n},Lyon\n"),
+                2 => "\n\r\n".to_string(),
+                3 => format!("user{n},\"a,b\",c\"d\n"),
+                _ => format!("user{n},Nice,{n}\r\n"),
+            };
+            content.extend(row.as_bytes());
+        }
+        content.extend(b"bad\xFF,Nice,1\nlast,Metz,2");
+
+        let path = std::env::temp_dir().join("csv_split_reader.csv");
+        std::fs::write(&path, &content).unwrap();
+        let path = path.to_str().unwrap();
+
+        let sequential = read_all(CsvReader::new(path, 50).unwrap());
+        for range_bytes in [1, 97, 1000] {
+            let split = SplitCsvReader::with_range_bytes(path, 50, &CsvDialect::default(), 3, Some(range_bytes)).unwrap();
+            assert!(split.ranges.len() > 1);
+            assert_eq!(read_all(split), sequential);
+        }
+        assert_eq!(sequential.1.len(), 30);
+    }
+}
@@ -18,4 +18,123 @@ impl std::fmt::Display for ValidationError {
             ValidationError::TooLong(field, max) => write!(f, "{} too long (maximum: {} chars)", field, max),
         }
     }
-}
\ No newline at end of file
+}
+
+pub type EtlResult<T> = Result<T, EtlError>;
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum EtlError {
+    Io { path: String, source: std::io::Error },
+    Csv { path: String, source: csv::Error },
+    Json { path: String, source: serde_json::Error },
+    InvalidSource { path: String, reason: String },
+    #[cfg(feature = "sqlite")]
+    Sqlite { path: String, source: rusqlite::Error },
+    #[cfg(feature = "parquet")]
+    Parquet { path: String, source: parquet::errors::ParquetError },
+    #[cfg(feature = "xlsx")]
+    Xlsx { path: String, source: Box<dyn std::error::Error + Send + Sync> },
+    Recipe { path: String, source: serde_yaml::Error },
+    InvalidRecipe(String),
+    UnknownStep { index: usize, action: String, value: String },
+    UnsupportedStep { name: String, reason: String },
+    Step { index: usize, action: String, source: Box<EtlError> },
+    #[cfg(feature = "script")]
+    Script { source: Box<dyn std::error::Error + Send + Sync> },
+    #[cfg(feature = "wasm")]
+    Plugin { path: String, reason: String },
+    NoSource,
+}
+
+impl EtlError {
+    // Des octets invalides (encodage strict, UTF-8) mettent en cause la source, pas le disque
+    pub fn io(path: &str, source: std::io::Error) -> Self {
+        if is_invalid_data(&source) {
+            return EtlError::InvalidSource { path: path.to_string(), reason: source.to_string() };
+        }
+        EtlError::Io { path: path.to_string(), source }
+    }
+
+    pub fn csv(path: &str, source: csv::Error) -> Self {
+        EtlError::Csv { path: path.to_string(), source }
+    }
+
+    #[cfg(feature = "sqlite")]
+    pub fn sqlite(path: &str, source: rusqlite::Error) -> Self {
+        EtlError::Sqlite { path: path.to_string(), source }
+    }
+
+    #[cfg(feature = "parquet")]
+    pub fn parquet(path: &str, source: parquet::errors::ParquetError) -> Self {
+        EtlError::Parquet { path: path.to_string(), source }
+    }
+
+    // Ajoute l'index et l'action de l'étape de recette qui a échoué
+    pub fn in_step(self, index: usize, action: &str) -> Self {
+        EtlError::Step { index, action: action.to_string(), source: Box::new(self) }
+    }
+}
+
+// Le parser CSV enveloppe l'erreur de lecture dans un `csv::Error`
+fn is_invalid_data(err: &std::io::Error) -> bool {
+    let inner = err.get_ref()
+        .and_then(|inner| inner.downcast_ref::<csv::Error>())
+        .and_then(|inner| match inner.kind() {
+            csv::ErrorKind::Io(inner) => Some(inner.kind()),
+            _ => None,
+        });
+
+    err.kind() == std::io::ErrorKind::InvalidData || inner == Some(std::io::ErrorKind::InvalidData)
+}
+
+impl std::fmt::Display for EtlError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            EtlError::Io { path, .. } => write!(f, "I/O error on {}", path),
+            EtlError::Csv { path, source } => match source.position() {
+                Some(pos) => write!(f, "CSV error in {} at line {}", path, pos.line()),
+                None => write!(f, "CSV error in {}", path),
+            },
+            EtlError::Json { path, source } => write!(f, "JSON error in {} at line {}", path, source.line()),
+            EtlError::InvalidSource { path, reason } => write!(f, "invalid source {}: {}", path, reason),
+            #[cfg(feature = "sqlite")]
+            EtlError::Sqlite { path, .. } => write!(f, "SQLite error on {}", path),
+            #[cfg(feature = "parquet")]
+            EtlError::Parquet { path, .. } => write!(f, "Parquet error on {}", path),
+            #[cfg(feature = "xlsx")]
+            EtlError::Xlsx { path, .. } => write!(f, "Excel error on {}", path),
+            EtlError::Recipe { path, .. } => write!(f, "invalid recipe file {}", path),
+            EtlError::InvalidRecipe(reason) => write!(f, "invalid recipe: {}", reason),
+            EtlError::UnknownStep { index, action, value } => write!(f, "step {}: unknown {} `{}`", index, action, value),
+            EtlError::UnsupportedStep { name, reason } => write!(f, "step `{}` is not supported here: {}", name, reason),
+            EtlError::Step { index, action, .. } => write!(f, "step {} ({}) failed", index, action),
+            #[cfg(feature = "script")]
+            EtlError::Script { .. } => write!(f, "script error"),
+            #[cfg(feature = "wasm")]
+            EtlError::Plugin { path, reason } => write!(f, "plugin {}: {}", path, reason),
+            EtlError::NoSource => write!(f, "no sources provided"),
+        }
+    }
+}
+
+impl std::error::Error for EtlError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            EtlError::Io { source, .. } => Some(source),
+            EtlError::Csv { source, .. } => Some(source),
+            EtlError::Json { source, .. } => Some(source),
+            #[cfg(feature = "sqlite")]
+            EtlError::Sqlite { source, .. } => Some(source),
+            #[cfg(feature = "parquet")]
+            EtlError::Parquet { source, .. } => Some(source),
+            #[cfg(feature = "xlsx")]
+            EtlError::Xlsx { source, .. } => Some(source.as_ref()),
+            EtlError::Recipe { source, .. } => Some(source),
+            EtlError::Step { source, .. } => Some(source.as_ref()),
+            #[cfg(feature = "script")]
+            EtlError::Script { source } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use std::sync::{Arc, Mutex};
+use crate::models::error::EtlResult;
+use crate::models::pipeline::PipelineStats;
+
+pub const DEFAULT_CHUNK_SIZE: usize = 1000;
+
+// Pendant d'`OutputPort` côté source : chaque adapter produit des paquets de
+// records, quel que soit le format lu.
+pub trait InputPort: Send {
+    /// Lit le paquet suivant, `Ok(None)` en fin de source.
+    /// Une erreur renvoyée ici est bloquante (fichier illisible...).
+    fn read_chunk(&mut self) -> EtlResult<Option<Vec<csv::StringRecord>>>;
+
+    /// Erreurs non bloquantes (lignes invalides...) accumulées depuis le dernier appel.
+    fn take_errors(&mut self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Caractères remplacés au transcodage depuis le dernier appel.
+    fn take_replaced_chars(&mut self) -> usize {
+        0
+    }
+
+    /// Lignes ignorées car ne correspondant pas au format attendu, depuis le dernier appel.
+    fn take_unmatched_lines(&mut self) -> usize {
+        0
+    }
+}
+
+impl<P: InputPort + ?Sized> InputPort for Box<P> {
+    fn read_chunk(&mut self) -> EtlResult<Option<Vec<csv::StringRecord>>> {
+        (**self).read_chunk()
+    }
+
+    fn take_errors(&mut self) -> Vec<String> {
+        (**self).take_errors()
+    }
+
+    fn take_replaced_chars(&mut self) -> usize {
+        (**self).take_replaced_chars()
+    }
+
+    fn take_unmatched_lines(&mut self) -> usize {
+        (**self).take_unmatched_lines()
+    }
+}
+
+// Adapte un `InputPort` en itérateur de chunks pour `StreamingPipeline`.
+// Les erreurs et compteurs de la source sont reportés dans le puits partagé
+// du pipeline ; une erreur bloquante y est gardée et arrête l'itération.
+pub struct InputChunks<P: InputPort> {
+    port: P,
+    sink: Arc<Mutex<PipelineStats>>,
+    done: bool,
+}
+
+impl<P: InputPort> InputChunks<P> {
+    pub fn new(port: P, sink: Arc<Mutex<PipelineStats>>) -> Self {
+        InputChunks { port, sink, done: false }
+    }
+}
+
+impl<P: InputPort> Iterator for InputChunks<P> {
+    type Item = Vec<csv::StringRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let result = self.port.read_chunk();
+
+        let errors = self.port.take_errors();
+        let replaced_chars = self.port.take_replaced_chars();
+        let unmatched_lines = self.port.take_unmatched_lines();
+        let mut sink = self.sink.lock().unwrap();
+        sink.push_errors(errors);
+        sink.replaced_chars += replaced_chars;
+        sink.unmatched_lines += unmatched_lines;
+
+        let chunk = match result {
+            Ok(chunk) => chunk,
+            Err(err) => {
+                sink.set_fatal(err);
+                None
+            }
+        };
+
+        self.done = chunk.is_none();
+        chunk
+    }
+}
+
+// Enchaîne plusieurs sources, lues l'une après l'autre
+pub struct ChainedInput {
+    ports: Vec<Box<dyn InputPort>>,
+    current_index: usize,
+}
+
+impl ChainedInput {
+    pub fn new(ports: Vec<Box<dyn InputPort>>) -> Self {
+        ChainedInput { ports, current_index: 0 }
+    }
+}
+
+impl InputPort for ChainedInput {
+    fn read_chunk(&mut self) -> EtlResult<Option<Vec<csv::StringRecord>>> {
+        while self.current_index < self.ports.len() {
+            match self.ports[self.current_index].read_chunk()? {
+                Some(chunk) => return Ok(Some(chunk)),
+                None => self.current_index += 1,
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn take_errors(&mut self) -> Vec<String> {
+        self.ports.iter_mut()
+            .flat_map(|port| port.take_errors())
+            .collect()
+    }
+
+    fn take_replaced_chars(&mut self) -> usize {
+        self.ports.iter_mut().map(|port| port.take_replaced_chars()).sum()
+    }
+
+    fn take_unmatched_lines(&mut self) -> usize {
+        self.ports.iter_mut().map(|port| port.take_unmatched_lines()).sum()
+    }
+}
@@ -0,0 +1,235 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::hash::Hash;
+use rayon::iter::Either;
+use rayon::prelude::*;
+use crate::models::error::EtlResult;
+use crate::models::pipeline::Pipeline;
+use crate::models::spill::Spill;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operator {
+    Transform,
+    Filter,
+}
+
+// Record retiré par l'opérateur `op` (filtre ou transformation en erreur)
+struct Dropped {
+    op: usize,
+    error: Option<String>,
+}
+
+type Plan<'a, S, T> = Box<dyn Fn(usize, S) -> Result<T, Dropped> + Send + Sync + 'a>;
+
+// Enregistre les opérateurs au lieu de les exécuter : les map/filter successifs
+// sont composés en une seule fonction par record, appliquée en une passe
+// parallèle par `collect`. `try_transform_chunks` et les points de
+// matérialisation (`sort_by`, `aggregate`, `merge`) forcent l'exécution.
+pub struct LazyPipeline<'a, S, T> {
+    source: Pipeline<S>,
+    plan: Plan<'a, S, T>,
+    ops: Vec<Operator>,
+}
+
+impl<T: Send> Pipeline<T> {
+    pub fn lazy<'a>(self) -> LazyPipeline<'a, T, T>
+    where
+        T: 'a
+    {
+        LazyPipeline {
+            source: self,
+            plan: Box::new(|_, item| Ok(item)),
+            ops: Vec::new(),
+        }
+    }
+}
+
+impl<'a, S, T> LazyPipeline<'a, S, T>
+where
+    S: Send + 'a,
+    T: Send + 'a
+{
+    // Ajoute un opérateur : `None` retire le record, `Some(Err)` le retire avec une erreur
+    fn then<U, G>(mut self, operator: Operator, step: G) -> LazyPipeline<'a, S, U>
+    where
+        G: Fn(T) -> Option<Result<U, String>> + Send + Sync + 'a,
+        U: Send + 'a
+    {
+        let op = self.ops.len();
+        self.ops.push(operator);
+        let plan = self.plan;
+
+        LazyPipeline {
+            source: self.source,
+            plan: Box::new(move |idx, item| {
+                match step(plan(idx, item)?) {
+                    Some(Ok(item)) => Ok(item),
+                    Some(Err(err)) => Err(Dropped {
+                        op,
+                        error: Some(format!("{} record transform error: {}", idx, err)),
+                    }),
+                    None => Err(Dropped { op, error: None }),
+                }
+            }),
+            ops: self.ops,
+        }
+    }
+
+    pub fn transform<F, U>(self, transform: F) -> LazyPipeline<'a, S, U>
+    where
+        F: Fn(T) -> U + Sync + Send + 'a,
+        U: Send + 'a
+    {
+        self.then(Operator::Transform, move |item| Some(Ok(transform(item))))
+    }
+
+    /// L'index des erreurs est celui du record dans les données sources.
+    pub fn try_transform<F, U>(self, transform: F) -> LazyPipeline<'a, S, U>
+    where
+        F: Fn(T) -> Result<U, String> + Sync + Send + 'a,
+        U: Send + 'a
+    {
+        self.then(Operator::Transform, move |item| Some(transform(item)))
+    }
+
+    pub fn transform_if<F, P>(self, predicate: P, transform: F) -> LazyPipeline<'a, S, T>
+    where
+        P: Fn(&T) -> bool + Sync + Send + 'a,
+        F: Fn(T) -> T + Sync + Send + 'a
+    {
+        self.then(Operator::Transform, move |item| {
+            Some(Ok(if predicate(&item) { transform(item) } else { item }))
+        })
+    }
+
+    pub fn filter<F>(self, predicate: F) -> LazyPipeline<'a, S, T>
+    where
+        F: Fn(&T) -> bool + Sync + Send + 'a
+    {
+        self.then(Operator::Filter, move |item| predicate(&item).then_some(Ok(item)))
+    }
+
+    // Opère sur des paquets : exécute le plan en cours puis repart d'un plan vide
+    pub fn try_transform_chunks<F, U>(self, chunk_size: usize, transform: F) -> LazyPipeline<'a, U, U>
+    where
+        F: Fn(Vec<T>) -> Result<Vec<U>, String> + Sync + Send,
+        T: Sync,
+        U: Send + 'a
+    {
+        self.collect()
+            .try_transform_chunks(chunk_size, transform)
+            .lazy()
+    }
+
+    pub fn collect(self) -> Pipeline<T> {
+        let plan = self.plan;
+        let mut stats = self.source.stats;
+        let budget = self.source.budget;
+
+        let (data, dropped): (Vec<T>, Vec<Dropped>) = self.source.data
+            .into_par_iter()
+            .enumerate()
+            .map(|(idx, item)| plan(idx, item))
+            .partition_map(|result| match result {
+                Ok(item) => Either::Left(item),
+                Err(dropped) => Either::Right(dropped),
+            });
+
+        // Mêmes compteurs qu'en exécutant les opérateurs un à un : records
+        // encore présents après la dernière transformation / le dernier filtre
+        let remaining_after = |op: usize| data.len() + dropped.iter().filter(|d| d.op > op).count();
+        if let Some(op) = self.ops.iter().rposition(|o| *o == Operator::Transform) {
+            stats.total_transformed = remaining_after(op);
+        }
+        if let Some(op) = self.ops.iter().rposition(|o| *o == Operator::Filter) {
+            stats.total_filtered = remaining_after(op);
+        }
+        stats.push_errors(dropped.into_iter().filter_map(|d| d.error));
+
+        Pipeline { data, stats, budget }
+    }
+}
+
+// Résultat d'une étape ajoutée à un plan : fusionnée au plan, ou barrière
+// (`try_transform_chunks`, tri...) qui l'a exécuté et repart d'un plan vide
+pub enum Planned<'a, S, T> {
+    Fused(LazyPipeline<'a, S, T>),
+    Collected(LazyPipeline<'a, T, T>),
+}
+
+impl<'a, S: Send + 'a, T: Send + 'a> Planned<'a, S, T> {
+    pub fn collect(self) -> Pipeline<T> {
+        match self {
+            Planned::Fused(pipeline) => pipeline.collect(),
+            Planned::Collected(pipeline) => pipeline.collect(),
+        }
+    }
+}
+
+// Points de matérialisation : le plan est exécuté, puis l'opération appliquée
+impl<'a, S, T> LazyPipeline<'a, S, T>
+where
+    S: Send + 'a,
+    T: Send + Sync + 'a
+{
+    pub fn sort_by<F>(self, compare: F) -> EtlResult<Pipeline<T>>
+    where
+        T: Spill,
+        F: Fn(&T, &T) -> Ordering + Sync
+    {
+        self.collect().sort_by(compare)
+    }
+
+    pub fn aggregate<K>(self, key_fn: impl Fn(&T) -> K + Sync + Send) -> EtlResult<HashMap<K, usize>>
+    where
+        K: Ord + Hash + Spill + Send
+    {
+        self.collect().aggregate(key_fn)
+    }
+
+    pub fn merge(self, other: Pipeline<T>) -> Pipeline<T> {
+        self.collect().merge(other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fused_plan_matches_step_by_step() {
+        let source = || Pipeline { data: (0..1000).collect::<Vec<i64>>(), ..Default::default() };
+
+        let fused = source().lazy()
+            .transform(|n| n * 3)
+            .filter(|n| n % 2 == 0)
+            .try_transform(|n| if n % 9 == 0 { Err("multiple de 9".to_string()) } else { Ok(n + 1) })
+            .transform_if(|n| *n > 100, |n| -n)
+            .filter(|n| *n != 7)
+            .collect();
+
+        let expected: Vec<i64> = (0..1000)
+            .map(|n| n * 3)
+            .filter(|n| n % 2 == 0 && n % 9 != 0)
+            .map(|n| if n + 1 > 100 { -(n + 1) } else { n + 1 })
+            .filter(|n| *n != 7)
+            .collect();
+
+        assert_eq!(fused.data, expected);
+
+        // Mêmes données et compteurs qu'opérateur par opérateur sur `Pipeline`
+        let chained = source()
+            .transform(|n| n * 3)
+            .filter(|n| n % 2 == 0)
+            .try_transform(|n| if n % 9 == 0 { Err("multiple de 9".to_string()) } else { Ok(n + 1) })
+            .transform_if(|n| *n > 100, |n| -n)
+            .filter(|n| *n != 7);
+        assert_eq!(chained.data, expected);
+        assert_eq!(chained.stats.total_transformed, fused.stats.total_transformed);
+        assert_eq!(fused.stats.total_filtered, expected.len());
+        // 7 n'est atteint qu'après la dernière transformation
+        assert_eq!(fused.stats.total_transformed, expected.len() + 1);
+        assert_eq!(fused.stats.errors().len(), 500 / 3 + 1);
+        assert!(fused.stats.errors()[0].starts_with("0 record transform error"));
+    }
+}
@@ -1,9 +1,23 @@
 pub mod user;
 pub mod pipeline;
+pub mod lazy_pipeline;
+pub mod spill;
+pub mod sort;
 pub mod error;
 pub mod csv_reader;
+pub mod csv_byte_reader;
+pub mod csv_split_reader;
+pub mod csv_dialect;
 pub mod stream_pipeline;
 pub mod csv_multi_reader;
 pub mod output;
+pub mod record;
+pub mod input;
+pub mod parallel_input;
+pub mod stage;
 pub mod recipe_config;
-pub mod registry;
\ No newline at end of file
+pub mod registry;
+#[cfg(feature = "script")]
+pub mod script;
+#[cfg(feature = "wasm")]
+pub mod wasm_plugin;
\ No newline at end of file
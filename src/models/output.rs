@@ -1,8 +1,8 @@
-use std::error::Error;
+use crate::models::error::EtlResult;
 
 pub trait OutputPort<T> {
-    fn write(&mut self, data: &[T]) -> Result<(), Box<dyn Error>>;
-    fn finalize(&mut self) -> Result<(), Box<dyn Error>> {
+    fn write(&mut self, data: &[T]) -> EtlResult<()>;
+    fn finalize(&mut self) -> EtlResult<()> {
         Ok(())
     }
 }
\ No newline at end of file
@@ -0,0 +1,238 @@
+use std::collections::VecDeque;
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use serde::Deserialize;
+use crate::models::error::EtlResult;
+use crate::models::input::{ChainedInput, InputPort};
+
+// Chunks en attente par fichier (ordre conservé) ou au total (sans ordre)
+const CHANNEL_CAPACITY: usize = 4;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ParallelOptions {
+    // Nombre de fichiers lus en même temps, le nombre de cœurs par défaut
+    #[serde(default = "default_parallelism")]
+    pub parallelism: usize,
+    // Rend les chunks fichier par fichier, dans l'ordre des sources ; sinon dès qu'ils sont prêts
+    #[serde(default = "default_preserve_order")]
+    pub preserve_order: bool,
+    // Découpe chaque fichier CSV en plages parsées sur `parallelism` threads
+    #[serde(default)]
+    pub split_files: bool,
+}
+
+fn default_parallelism() -> usize {
+    thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+fn default_preserve_order() -> bool {
+    true
+}
+
+impl Default for ParallelOptions {
+    fn default() -> Self {
+        ParallelOptions {
+            parallelism: default_parallelism(),
+            preserve_order: default_preserve_order(),
+            split_files: false,
+        }
+    }
+}
+
+impl ParallelOptions {
+    pub fn sequential() -> Self {
+        ParallelOptions { parallelism: 1, preserve_order: true, split_files: false }
+    }
+
+    // Lecture parallèle si elle a un intérêt, sinon enchaînement simple des sources
+    pub fn chain(&self, ports: Vec<Box<dyn InputPort>>) -> Box<dyn InputPort> {
+        if self.parallelism <= 1 || ports.len() <= 1 {
+            Box::new(ChainedInput::new(ports))
+        } else {
+            Box::new(ParallelInput::new(ports, self))
+        }
+    }
+}
+
+// Ce qu'un thread de lecture transmet : un chunk avec les erreurs et compteurs associés
+struct Batch {
+    chunk: Vec<csv::StringRecord>,
+    errors: Vec<String>,
+    replaced_chars: usize,
+    unmatched_lines: usize,
+}
+
+type Message = EtlResult<Batch>;
+
+// Lit plusieurs sources sur `parallelism` threads. Chaque thread prend la
+// prochaine source dans l'ordre et la lit en entier ; les canaux sont bornés,
+// un lecteur en avance attend donc que le consommateur le rattrape.
+pub struct ParallelInput {
+    // Un canal par source si l'ordre est conservé, un seul sinon
+    receivers: VecDeque<Receiver<Message>>,
+    errors: Vec<String>,
+    replaced_chars: usize,
+    unmatched_lines: usize,
+}
+
+impl ParallelInput {
+    pub fn new(ports: Vec<Box<dyn InputPort>>, options: &ParallelOptions) -> Self {
+        let workers = options.parallelism.clamp(1, ports.len().max(1));
+        let mut receivers = VecDeque::new();
+        let mut queue = VecDeque::new();
+
+        if options.preserve_order {
+            for port in ports {
+                let (sender, receiver) = sync_channel(CHANNEL_CAPACITY);
+                queue.push_back((port, sender));
+                receivers.push_back(receiver);
+            }
+        } else {
+            let (sender, receiver) = sync_channel(CHANNEL_CAPACITY * workers);
+            for port in ports {
+                queue.push_back((port, sender.clone()));
+            }
+            receivers.push_back(receiver);
+        }
+
+        let queue = Arc::new(Mutex::new(queue));
+        for _ in 0..workers {
+            let queue = queue.clone();
+            thread::spawn(move || {
+                loop {
+                    let next = queue.lock().unwrap().pop_front();
+                    match next {
+                        Some((port, sender)) => drain(port, sender),
+                        None => break,
+                    }
+                }
+            });
+        }
+
+        ParallelInput {
+            receivers,
+            errors: Vec::new(),
+            replaced_chars: 0,
+            unmatched_lines: 0,
+        }
+    }
+}
+
+// S'arrête au premier échec d'envoi : le consommateur a été abandonné
+fn drain(mut port: Box<dyn InputPort>, sender: SyncSender<Message>) {
+    loop {
+        let message = port.read_chunk().map(|chunk| {
+            let done = chunk.is_none();
+            let batch = Batch {
+                chunk: chunk.unwrap_or_default(),
+                errors: port.take_errors(),
+                replaced_chars: port.take_replaced_chars(),
+                unmatched_lines: port.take_unmatched_lines(),
+            };
+            (batch, done)
+        });
+
+        match message {
+            Ok((batch, done)) => {
+                let empty = batch.chunk.is_empty() && batch.errors.is_empty()
+                    && batch.replaced_chars == 0 && batch.unmatched_lines == 0;
+                if (!empty && sender.send(Ok(batch)).is_err()) || done {
+                    return;
+                }
+            },
+            Err(err) => {
+                let _ = sender.send(Err(err));
+                return;
+            },
+        }
+    }
+}
+
+impl InputPort for ParallelInput {
+    fn read_chunk(&mut self) -> EtlResult<Option<Vec<csv::StringRecord>>> {
+        while let Some(receiver) = self.receivers.front() {
+            match receiver.recv() {
+                Ok(message) => {
+                    let batch = message?;
+                    self.errors.extend(batch.errors);
+                    self.replaced_chars += batch.replaced_chars;
+                    self.unmatched_lines += batch.unmatched_lines;
+                    if !batch.chunk.is_empty() {
+                        return Ok(Some(batch.chunk));
+                    }
+                },
+                // Tous les émetteurs de ce canal ont terminé
+                Err(_) => {
+                    self.receivers.pop_front();
+                },
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn take_errors(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.errors)
+    }
+
+    fn take_replaced_chars(&mut self) -> usize {
+        std::mem::take(&mut self.replaced_chars)
+    }
+
+    fn take_unmatched_lines(&mut self) -> usize {
+        std::mem::take(&mut self.unmatched_lines)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Source factice : `count` chunks d'un record `"{id}-{n}"`
+    struct Numbered {
+        id: usize,
+        count: usize,
+        sent: usize,
+    }
+
+    impl InputPort for Numbered {
+        fn read_chunk(&mut self) -> EtlResult<Option<Vec<csv::StringRecord>>> {
+            if self.sent == self.count {
+                return Ok(None);
+            }
+            self.sent += 1;
+            // Les premières sources sont les plus lentes
+            thread::sleep(std::time::Duration::from_millis((5 - self.id as u64) * 2));
+            Ok(Some(vec![csv::StringRecord::from(vec![format!("{}-{}", self.id, self.sent)])]))
+        }
+    }
+
+    fn ports() -> Vec<Box<dyn InputPort>> {
+        (0..5).map(|id| Box::new(Numbered { id, count: 10, sent: 0 }) as Box<dyn InputPort>).collect()
+    }
+
+    fn read_all(mut input: Box<dyn InputPort>) -> Vec<String> {
+        let mut values = Vec::new();
+        while let Some(chunk) = input.read_chunk().unwrap() {
+            values.extend(chunk.iter().map(|record| record[0].to_string()));
+        }
+        values
+    }
+
+    #[test]
+    fn test_parallel_input_ordering() {
+        let sequential = read_all(ParallelOptions::sequential().chain(ports()));
+
+        let ordered = ParallelOptions { parallelism: 3, preserve_order: true, split_files: false };
+        assert_eq!(read_all(ordered.chain(ports())), sequential);
+
+        let unordered = ParallelOptions { parallelism: 3, preserve_order: false, split_files: false };
+        let mut values = read_all(unordered.chain(ports()));
+        assert_eq!(values.len(), 50);
+        values.sort();
+        let mut expected = sequential;
+        expected.sort();
+        assert_eq!(values, expected);
+    }
+}
@@ -1,38 +1,167 @@
+use std::cmp::Ordering;
 use std::collections::HashMap;
 use rayon::prelude::*;
+use crate::models::csv_dialect::CsvDialect;
+use crate::models::csv_reader::CsvReader;
+use crate::models::csv_split_reader::SplitCsvReader;
+use crate::models::error::{EtlError, EtlResult};
+use crate::models::input::{InputPort, DEFAULT_CHUNK_SIZE};
+use crate::models::spill::{count_items, dedup_items, sort_items, MemoryBudget, Spill};
+use crate::models::stage::StageStats;
 
 #[derive(Debug, Default)]
 pub struct PipelineStats {
     pub total_extracted: usize,
     pub total_transformed: usize,
     pub total_filtered: usize,
-    errors: Vec<String>
+    // Caractères remplacés par U+FFFD lors du transcodage (`lossy_encoding`)
+    pub replaced_chars: usize,
+    // Lignes de log ne correspondant pas à la regex de la source
+    pub unmatched_lines: usize,
+    // Séries triées écrites sur disque par les opérateurs au-delà du budget mémoire
+    pub spilled_runs: usize,
+    pub spilled_bytes: u64,
+    errors: Vec<String>,
+    // Fichiers effectivement lus, après résolution des motifs et répertoires
+    sources: Vec<String>,
+    // Files entre les étapes d'un `StreamingPipeline`, dans l'ordre du flux
+    stages: Vec<StageStats>,
+    // Erreur bloquante d'une étape paresseuse, rendue par `StreamingPipeline::load`
+    fatal: Option<EtlError>,
+}
+
+impl PipelineStats {
+    pub fn errors(&self) -> &[String] {
+        &self.errors
+    }
+
+    pub fn push_errors(&mut self, errors: impl IntoIterator<Item = String>) {
+        self.errors.extend(errors);
+    }
+
+    pub fn sources(&self) -> &[String] {
+        &self.sources
+    }
+
+    pub fn set_sources(&mut self, sources: Vec<String>) {
+        self.sources = sources;
+    }
+
+    pub fn stages(&self) -> &[StageStats] {
+        &self.stages
+    }
+
+    pub fn push_stage(&mut self, stage: StageStats) {
+        self.stages.push(stage);
+    }
+
+    // Seule la première erreur bloquante est gardée : elle a arrêté le flux
+    pub fn set_fatal(&mut self, err: EtlError) {
+        self.fatal.get_or_insert(err);
+    }
+
+    pub fn take_fatal(&mut self) -> Option<EtlError> {
+        self.fatal.take()
+    }
+
+    pub fn merge(&mut self, other: PipelineStats) {
+        self.total_extracted += other.total_extracted;
+        self.total_transformed += other.total_transformed;
+        self.total_filtered += other.total_filtered;
+        self.replaced_chars += other.replaced_chars;
+        self.unmatched_lines += other.unmatched_lines;
+        self.spilled_runs += other.spilled_runs;
+        self.spilled_bytes += other.spilled_bytes;
+        self.errors.extend(other.errors);
+        self.sources.extend(other.sources);
+        self.stages.extend(other.stages);
+        if let Some(err) = other.fatal {
+            self.set_fatal(err);
+        }
+    }
+
+    pub fn report(&self) {
+        // Sur stderr : stdout peut porter les données (`path: "-"`)
+        eprintln!("=== Pipeline Statistics ===");
+        if !self.sources.is_empty() {
+            eprintln!("📂 Sources: {}", self.sources.len());
+            for source in &self.sources {
+                eprintln!("   - {}", source);
+            }
+        }
+        eprintln!("📥 Extracted: {}", self.total_extracted);
+        eprintln!("🔄 Transformed: {}", self.total_transformed);
+        eprintln!("✅ Filtered (kept): {}", self.total_filtered);
+        eprintln!("❌ Rejected: {}", self.total_transformed.saturating_sub(self.total_filtered));
+        if self.unmatched_lines > 0 {
+            eprintln!("🚫 Unmatched lines: {}", self.unmatched_lines);
+        }
+        if self.replaced_chars > 0 {
+            eprintln!("🔤 Replaced characters: {}", self.replaced_chars);
+        }
+        if self.spilled_runs > 0 {
+            eprintln!("💾 Spilled: {} runs, {} bytes", self.spilled_runs, self.spilled_bytes);
+        }
+        for stage in &self.stages {
+            eprintln!(
+                "🧵 Stage {}: {} chunks, queue depth max {}/{} (mean {:.1}), producer stalled {:?}, consumer waited {:?}",
+                stage.name, stage.chunks, stage.max_depth, stage.capacity, stage.mean_depth(),
+                stage.send_stall, stage.recv_stall
+            );
+        }
+        if !self.errors.is_empty() {
+            eprintln!("⚠️  Errors: {}", self.errors.len());
+            for err in &self.errors {
+                eprintln!("   - {}", err);
+            }
+        }
+    }
 }
 
 
 pub struct Pipeline<T> {
     pub data: Vec<T>,
-    pub stats: PipelineStats
+    pub stats: PipelineStats,
+    // Mémoire allouée à `sort_by`, `dedup_by` et `aggregate` ; illimitée si absent
+    pub budget: Option<MemoryBudget>,
+}
+
+impl<T> Default for Pipeline<T> {
+    fn default() -> Self {
+        Pipeline { data: Vec::new(), stats: PipelineStats::default(), budget: None }
+    }
 }
 
 impl Pipeline<csv::StringRecord> {
-    pub fn extract(source: &str) -> Result<Self, Box<dyn std::error::Error>> {
-        let mut reader = csv::ReaderBuilder::new()
-            .from_path(source)?;
+    pub fn extract(source: &str) -> EtlResult<Self> {
+        let mut pipeline = Pipeline::from_input(CsvReader::new(source, DEFAULT_CHUNK_SIZE)?)?;
+        pipeline.stats.set_sources(vec![source.to_string()]);
+        Ok(pipeline)
+    }
 
+    // Comme `extract`, en parsant le fichier par plages sur tous les cœurs ;
+    // même résultat et mêmes erreurs. Repli sur `extract` si le fichier ne se découpe pas.
+    pub fn extract_parallel(source: &str) -> EtlResult<Self> {
+        if !SplitCsvReader::can_split(source, &Default::default()) {
+            return Pipeline::extract(source);
+        }
+        let parallelism = std::thread::available_parallelism().map_or(1, |n| n.get());
+        let reader = SplitCsvReader::new(source, DEFAULT_CHUNK_SIZE, &CsvDialect::default(), parallelism)?;
+
+        let mut pipeline = Pipeline::from_input(reader)?;
+        pipeline.stats.set_sources(vec![source.to_string()]);
+        Ok(pipeline)
+    }
+
+    pub fn from_input<P: InputPort>(mut port: P) -> EtlResult<Self> {
         let mut data = Vec::new();
         let mut errors = Vec::new();
 
-        for (idx, result) in reader.records().enumerate() {
-            match result {
-                Ok(record) => {
-                    data.push(record)
-                }
-                Err(err) => {
-                    errors.push(format!("{} record parse error: {}", idx, err));
-                }
-            }
+        while let Some(chunk) = port.read_chunk()? {
+            data.extend(chunk);
+            errors.extend(port.take_errors());
         }
+        errors.extend(port.take_errors());
 
         let default_stats = PipelineStats::default();
         let count = data.len();
@@ -42,33 +171,73 @@ impl Pipeline<csv::StringRecord> {
             stats: PipelineStats{
                 errors,
                 total_extracted: count,
+                replaced_chars: port.take_replaced_chars(),
+                unmatched_lines: port.take_unmatched_lines(),
                 ..default_stats
-            }
+            },
+            budget: None,
         })
     }
 }
 
 
+// Chaque opérateur fait sa propre passe ; `lazy()` fusionne une chaîne en une seule
 impl<T: Send + Sync> Pipeline<T> {
     pub fn transform<F, U>(self, transform: F) -> Pipeline<U>
     where
         F: Fn(T) -> U + Sync + Send,
-        T: Send,
         U: Send
     {
-        let transformed: Vec<U> = self.data
+        self.lazy().transform(transform).collect()
+    }
+
+    /// Comme `transform`, mais une erreur retire l'enregistrement du pipeline
+    /// et est ajoutée aux stats au lieu de paniquer.
+    pub fn try_transform<F, U>(self, transform: F) -> Pipeline<U>
+    where
+        F: Fn(T) -> Result<U, String> + Sync + Send,
+        U: Send
+    {
+        self.lazy().try_transform(transform).collect()
+    }
+
+    /// Applique `transform` sur des paquets de `chunk_size` éléments.
+    /// Un paquet en erreur est retiré en entier.
+    pub fn try_transform_chunks<F, U>(self, chunk_size: usize, transform: F) -> Pipeline<U>
+    where
+        F: Fn(Vec<T>) -> Result<Vec<U>, String> + Sync + Send,
+        U: Send
+    {
+        let chunk_size = chunk_size.max(1);
+        let mut chunks: Vec<Vec<T>> = Vec::new();
+        let mut items = self.data.into_iter().peekable();
+
+        while items.peek().is_some() {
+            chunks.push(items.by_ref().take(chunk_size).collect());
+        }
+
+        let results: Vec<Result<Vec<U>, String>> = chunks
             .into_par_iter()
-            .map(transform)
+            .enumerate()
+            .map(|(idx, chunk)| transform(chunk).map_err(|err| format!("{} chunk transform error: {}", idx, err)))
             .collect();
 
-        let count = transformed.len();
+        let mut transformed = Vec::new();
+        let mut stats = self.stats;
+
+        for result in results {
+            match result {
+                Ok(chunk) => transformed.extend(chunk),
+                Err(err) => stats.errors.push(err),
+            }
+        }
+
+        stats.total_transformed = transformed.len();
 
         Pipeline {
             data: transformed,
-            stats: PipelineStats {
-                total_transformed: count,
-                ..self.stats
-            }
+            stats,
+            budget: self.budget,
         }
     }
 
@@ -77,51 +246,97 @@ impl<T: Send + Sync> Pipeline<T> {
         P: Fn(&T) -> bool + Sync + Send,
         F: Fn(T) -> T + Sync + Send
     {
-        let transformed: Vec<T> = self.data
-            .into_par_iter()
-            .map(|item| {
-                if predicate(&item) {
-                    transform(item)
-                } else {
-                    item
-                }
-            })
-            .collect();
+        self.lazy().transform_if(predicate, transform).collect()
+    }
 
-        let count = transformed.len();
+    pub fn filter<F>(self, predicate: F) -> Pipeline<T>
+    where
+        F: Fn(&T) -> bool + Sync + Send
+    {
+        self.lazy().filter(predicate).collect()
+    }
 
-        Pipeline {
-            data: transformed,
-            stats: PipelineStats {
-                total_transformed: count,
-                ..self.stats
-            }
-        }
+    // Au-delà du budget, `sort_by`, `dedup_by` et `aggregate` déversent sur disque des
+    // séries triées au lieu de travailler en mémoire, puis les fusionnent
+    pub fn with_memory_budget(mut self, budget: MemoryBudget) -> Pipeline<T> {
+        self.budget = Some(budget);
+        self
     }
 
-    pub fn filter<F>(self, predicate: F) -> Pipeline<T>
-    where F: Fn(&T) -> bool + Sync + Send
+    pub fn estimated_size(&self) -> usize
+    where
+        T: Spill
     {
-        let filtered: Vec<T> = self.data
-            .into_par_iter()
-            .filter(predicate).collect();
+        self.data.par_iter().map(Spill::estimated_size).sum()
+    }
 
-        let count = filtered.len();
+    // Budget à respecter, si les données le dépassent
+    fn exceeded_budget(&self) -> Option<MemoryBudget>
+    where
+        T: Spill
+    {
+        self.budget.clone().filter(|budget| self.estimated_size() > budget.max_bytes)
+    }
 
-        Pipeline {
-            data: filtered,
-            stats: PipelineStats {
-                total_filtered: count,
-                ..self.stats
-            }
+    /// Tri stable, parallèle en mémoire. Au-delà du budget, les records quittent
+    /// `data` un à un vers des séries sur disque, fusionnées ensuite.
+    pub fn sort_by<F>(mut self, compare: F) -> EtlResult<Pipeline<T>>
+    where
+        T: Spill,
+        F: Fn(&T, &T) -> Ordering + Sync
+    {
+        let Some(budget) = self.exceeded_budget() else {
+            self.data.par_sort_by(compare);
+            return Ok(self);
+        };
+
+        let data = std::mem::take(&mut self.data);
+        self.data = sort_items(&mut data.into_iter(), budget, compare, &mut self.stats)?
+            .collect::<EtlResult<Vec<T>>>()?;
+        Ok(self)
+    }
+
+    /// Garde le premier de chaque groupe de records égaux selon `compare`, dans l'ordre d'origine.
+    pub fn dedup_by<F>(mut self, compare: F) -> EtlResult<Pipeline<T>>
+    where
+        T: Spill,
+        F: Fn(&T, &T) -> Ordering + Sync
+    {
+        if let Some(budget) = self.exceeded_budget() {
+            let data = std::mem::take(&mut self.data);
+            self.data = dedup_items(&mut data.into_iter(), budget, compare, &mut self.stats)?
+                .collect::<EtlResult<Vec<T>>>()?;
+            self.stats.total_filtered = self.data.len();
+            return Ok(self);
         }
+
+        // Tri stable des positions : chaque groupe commence par sa première occurrence
+        let mut order: Vec<usize> = (0..self.data.len()).collect();
+        order.par_sort_by(|&a, &b| compare(&self.data[a], &self.data[b]));
+
+        let mut keep = vec![false; self.data.len()];
+        for (pos, &idx) in order.iter().enumerate() {
+            keep[idx] = pos == 0 || compare(&self.data[order[pos - 1]], &self.data[idx]) != Ordering::Equal;
+        }
+
+        let mut keep = keep.into_iter();
+        self.data.retain(|_| keep.next().unwrap_or(false));
+        self.stats.total_filtered = self.data.len();
+        Ok(self)
     }
 
-    pub fn aggregate<K>(self, key_fn: impl Fn(&T) -> K + Sync + Send) -> HashMap<K, usize>
+    /// Avec un budget, la table des comptes ne le dépasse pas : les comptes
+    /// partiels sont déversés triés par clé puis additionnés à la fusion.
+    pub fn aggregate<K>(self, key_fn: impl Fn(&T) -> K + Sync + Send) -> EtlResult<HashMap<K, usize>>
     where
-        K: Eq + std::hash::Hash + Send + Clone
+        K: Ord + std::hash::Hash + Spill + Send
     {
-        self.data.par_iter()
+        if let Some(budget) = self.budget {
+            let mut stats = PipelineStats::default();
+            return count_items(&mut self.data.iter(), budget, |item| key_fn(item), &mut stats)?.collect();
+        }
+
+        Ok(self.data.par_iter()
             .fold(
                 || HashMap::new(),  // Chaque thread crée son HashMap
                 |mut map, item| {   // Chaque thread accumule dans SON map
@@ -139,32 +354,54 @@ impl<T: Send + Sync> Pipeline<T> {
                     }
                     map1
                 }
-            )
+            ))
     }
 
     pub fn merge(mut self, other: Pipeline<T>) -> Pipeline<T> {
         self.data.extend(other.data);
 
-        self.stats.total_extracted += other.stats.total_extracted;
-        self.stats.total_transformed += other.stats.total_transformed;
-        self.stats.total_filtered += other.stats.total_filtered;
-        self.stats.errors.extend(other.stats.errors);
+        self.stats.merge(other.stats);
 
         self
     }
 
     pub fn report(&self) {
-        println!("=== Pipeline Statistics ===");
-        println!("📥 Extracted: {}", self.stats.total_extracted);
-        println!("🔄 Transformed: {}", self.stats.total_transformed);
-        println!("✅ Filtered (kept): {}", self.stats.total_filtered);
-        println!("❌ Rejected: {}", self.stats.total_transformed - self.stats.total_filtered);
-        if !self.stats.errors.is_empty() {
-            println!("⚠️  Errors: {}", self.stats.errors.len());
-            for err in &self.stats.errors {
-                println!("   - {}", err);
-            }
-        }
+        self.stats.report();
     }
 
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::spill::test_spill_dir;
+
+    #[test]
+    fn test_budget_spills_batch_operators() -> EtlResult<()> {
+        let records: Vec<csv::StringRecord> = (0..500)
+            .map(|n| csv::StringRecord::from(vec![format!("ville{}", n % 13), format!("user{}", n)]))
+            .collect();
+        let dir = test_spill_dir("batch_spill");
+        let budget = MemoryBudget::new(1000).with_dir(&dir);
+        let pipeline = || Pipeline { data: records.clone(), ..Default::default() }.with_memory_budget(budget.clone());
+
+        let sorted = pipeline().sort_by(|a, b| a[0].cmp(&b[0]))?;
+        let mut expected = records.clone();
+        expected.sort_by(|a, b| a[0].cmp(&b[0]));
+        assert_eq!(sorted.data, expected);
+        assert!(sorted.stats.spilled_runs > 0);
+
+        let deduped = pipeline().dedup_by(|a, b| a[0].cmp(&b[0]))?;
+        assert_eq!(deduped.data, records[..13]);
+        assert!(deduped.stats.spilled_runs > 0);
+
+        // ~50 clés : la table des comptes dépasse le petit budget
+        let prefix = |r: &csv::StringRecord| r[1][..r[1].len() - 1].to_string();
+        let counts = pipeline().aggregate(prefix)?;
+        assert_eq!(counts, Pipeline { data: records.clone(), ..Default::default() }.aggregate(prefix)?);
+        assert_eq!(counts["user1"], 10);
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+        std::fs::remove_dir(&dir).unwrap();
+
+        Ok(())
+    }
+}
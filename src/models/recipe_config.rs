@@ -1,8 +1,44 @@
 use serde::Deserialize;
-use crate::models::pipeline::Pipeline;
+use crate::adapter::storage_input::fixed_width::{FixedWidthColumn, FixedWidthReader};
+use crate::adapter::storage_input::json::{FieldMapping, JsonLayout, JsonReader};
+use crate::adapter::storage_input::log::{preset_pattern, LogReader};
+use crate::adapter::storage_output::csv::CsvAdapter;
+use crate::adapter::storage_output::json::JsonAdapter;
+#[cfg(feature = "parquet")]
+use crate::adapter::storage_input::parquet::ParquetReader;
+#[cfg(feature = "sqlite")]
+use crate::adapter::storage_input::sqlite::{SqliteReader, DEFAULT_TABLE};
+#[cfg(feature = "xlsx")]
+use crate::adapter::storage_input::xlsx::XlsxReader;
+#[cfg(feature = "xml")]
+use crate::adapter::storage_input::xml::XmlReader;
+#[cfg(feature = "parquet")]
+use crate::adapter::storage_output::parquet::{ParquetAdapter, ParquetOptions};
+#[cfg(feature = "sqlite")]
+use crate::adapter::storage_output::sqlite::SqliteAdapter;
+#[cfg(feature = "xlsx")]
+use crate::adapter::storage_output::xlsx::XlsxAdapter;
+use crate::models::csv_dialect::CsvDialect;
+use crate::models::csv_multi_reader::MultiCsvReader;
+use crate::models::error::{EtlError, EtlResult};
+use crate::models::input::{InputPort, DEFAULT_CHUNK_SIZE};
+use crate::models::output::OutputPort;
+use crate::models::parallel_input::ParallelOptions;
+use crate::models::lazy_pipeline::{LazyPipeline, Planned};
+use crate::models::pipeline::{Pipeline, PipelineStats};
+use crate::models::record::Record;
 use crate::models::registry::{FilterFn, TransformFn};
+#[cfg(feature = "script")]
+use crate::models::script::{ScriptFn, ScriptLimits, ScriptMode};
+use crate::models::sort::{SortFields, SortKey, SortOrder};
+use crate::models::spill::MemoryBudget;
+use crate::models::stream_pipeline::{BoxedChunks, StreamingPipeline};
 use crate::models::user::User;
-use crate::utils::multi_extract::multi_extract;
+#[cfg(feature = "wasm")]
+use crate::models::wasm_plugin::{DEFAULT_FUEL, DEFAULT_MEMORY_LIMIT};
+use crate::utils::compression::{Compression, STDIO_PATH};
+use crate::utils::encoding::EncodingOptions;
+use crate::utils::resolve_paths::{resolve_paths, PathOptions};
 
 #[derive(Debug, Deserialize)]
 pub struct RecipeConfig {
@@ -10,73 +46,788 @@ pub struct RecipeConfig {
     pub source: SourceConfig,
     pub steps: Vec<StepConfig>,
     pub output: OutputConfig,
-
+    // Mémoire (en Mo) des étapes `sort`, `dedup` et `aggregate`, qui déversent sur
+    // disque au-delà. Si présent, `run` lit aussi la source en flux
+    pub memory_budget_mb: Option<usize>,
 }
 
 
 #[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum FormatFile {
-    CSV,
-    JSON,
-    SQLITE,
+    Csv,
+    Json,
+    Ndjson,
+    Sqlite,
+    Parquet,
+    Xlsx,
+    #[serde(rename = "fixed_width")]
+    FixedWidth,
+    Log,
+    Xml,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct SourceConfig {
     pub format: FormatFile,
+    // Fichiers, répertoires ou motifs glob, développés par `files`
     pub path: Vec<String>,
+    // `recursive`, `include` et `exclude` pour les répertoires et motifs
+    #[serde(flatten)]
+    pub path_options: PathOptions,
+    // Sources CSV : delimiter, quote, escape, comment, has_headers, flexible, trim, auto_detect
+    #[serde(flatten)]
+    pub dialect: CsvDialect,
+    // Sources texte : `encoding` (utf-8 par défaut, latin1, windows-1252...) et `lossy_encoding`
+    #[serde(flatten)]
+    pub encoding: EncodingOptions,
+    // Plusieurs fichiers : `parallelism` (nombre de cœurs par défaut) et `preserve_order` (vrai par défaut)
+    #[serde(flatten)]
+    pub parallel: ParallelOptions,
+    #[serde(default = "default_chunk_size")]
+    pub chunk_size: usize,
+    // Sources JSON : colonnes extraites par JSON pointer, sinon objets aplatis.
+    // Sources XML : chemins relatifs au record (`login`, `name/@first`)
+    #[serde(default)]
+    pub fields: Vec<FieldMapping>,
+    // Sources SQLite : requête à exécuter, sinon lecture de `table` (`users` par défaut)
+    pub query: Option<String>,
+    pub table: Option<String>,
+    // Sources Excel : feuille (la première par défaut) et plage `A1:D100`
+    pub sheet: Option<String>,
+    pub range: Option<String>,
+    // Sources à largeur fixe : nom, début (en caractères, à partir de 0) et largeur de chaque colonne
+    #[serde(default)]
+    pub columns: Vec<FixedWidthColumn>,
+    // Sources de logs : regex à groupes nommés, ou preset (common, combined, syslog)
+    pub pattern: Option<String>,
+    pub preset: Option<String>,
+    // Sources XML : chemin de l'élément répété, ex: `/users/user`
+    pub record_path: Option<String>,
+}
+
+impl FormatFile {
+    // Formats texte : lisibles depuis stdin ; csv, json et ndjson s'écrivent aussi sur stdout
+    pub fn is_text(self) -> bool {
+        matches!(self, FormatFile::Csv | FormatFile::Json | FormatFile::Ndjson | FormatFile::FixedWidth | FormatFile::Log | FormatFile::Xml)
+    }
+}
+
+fn default_chunk_size() -> usize {
+    DEFAULT_CHUNK_SIZE
+}
+
+impl SourceConfig {
+    // Choisit l'adapter de lecture selon `format`
+    pub fn open(&self) -> EtlResult<Box<dyn InputPort>> {
+        self.open_files(&self.files()?)
+    }
+
+    // Liste triée et dédoublonnée des fichiers désignés par `path`
+    pub fn files(&self) -> EtlResult<Vec<String>> {
+        let patterns: Vec<&str> = self.path.iter().map(|p| p.as_str()).collect();
+        resolve_paths(&patterns, &self.path_options)
+    }
+
+    pub fn open_files(&self, files: &[String]) -> EtlResult<Box<dyn InputPort>> {
+        if files.is_empty() {
+            return Err(EtlError::NoSource);
+        }
+
+        let paths: Vec<&str> = files.iter().map(|p| p.as_str()).collect();
+
+        if paths.contains(&STDIO_PATH) && !self.format.is_text() {
+            return Err(EtlError::InvalidRecipe(
+                format!("stdin (`-`) n'est pas supporté pour le format {:?}", self.format)
+            ));
+        }
+
+        match self.format {
+            FormatFile::Csv => Ok(Box::new(MultiCsvReader::with_options(&paths, self.chunk_size, &self.dialect, &self.encoding, &self.parallel)?)),
+            FormatFile::Json => self.open_json(&paths, JsonLayout::Array),
+            FormatFile::Ndjson => self.open_json(&paths, JsonLayout::Lines),
+            #[cfg(feature = "sqlite")]
+            FormatFile::Sqlite => self.open_sqlite(&paths),
+            #[cfg(not(feature = "sqlite"))]
+            FormatFile::Sqlite => Err(EtlError::InvalidRecipe(
+                "la source sqlite nécessite la feature `sqlite`".to_string()
+            )),
+            #[cfg(feature = "parquet")]
+            FormatFile::Parquet => {
+                let readers = paths.iter()
+                    .map(|path| Ok(Box::new(ParquetReader::new(path, self.chunk_size)?) as Box<dyn InputPort>))
+                    .collect::<EtlResult<Vec<_>>>()?;
+                Ok(self.parallel.chain(readers))
+            },
+            #[cfg(not(feature = "parquet"))]
+            FormatFile::Parquet => Err(EtlError::InvalidRecipe(
+                "la source parquet nécessite la feature `parquet`".to_string()
+            )),
+            #[cfg(feature = "xlsx")]
+            FormatFile::Xlsx => {
+                let readers = paths.iter()
+                    .map(|path| {
+                        let reader = XlsxReader::new(path, self.sheet.as_deref(), self.range.as_deref(), self.chunk_size)?;
+                        Ok(Box::new(reader) as Box<dyn InputPort>)
+                    })
+                    .collect::<EtlResult<Vec<_>>>()?;
+                Ok(self.parallel.chain(readers))
+            },
+            #[cfg(not(feature = "xlsx"))]
+            FormatFile::Xlsx => Err(EtlError::InvalidRecipe(
+                "la source xlsx nécessite la feature `xlsx`".to_string()
+            )),
+            FormatFile::FixedWidth => {
+                let readers = paths.iter()
+                    .map(|path| {
+                        let reader = FixedWidthReader::new(path, self.columns.clone(), self.chunk_size, &self.encoding)?;
+                        Ok(Box::new(reader) as Box<dyn InputPort>)
+                    })
+                    .collect::<EtlResult<Vec<_>>>()?;
+                Ok(self.parallel.chain(readers))
+            },
+            FormatFile::Log => {
+                let pattern = match (&self.pattern, &self.preset) {
+                    (Some(pattern), None) => pattern.as_str(),
+                    (None, Some(preset)) => preset_pattern(preset)
+                        .ok_or_else(|| EtlError::InvalidRecipe(format!("preset de log inconnu `{}`", preset)))?,
+                    _ => return Err(EtlError::InvalidRecipe(
+                        "une source log attend soit `pattern`, soit `preset`".to_string()
+                    )),
+                };
+                let readers = paths.iter()
+                    .map(|path| Ok(Box::new(LogReader::new(path, pattern, self.chunk_size, &self.encoding)?) as Box<dyn InputPort>))
+                    .collect::<EtlResult<Vec<_>>>()?;
+                Ok(self.parallel.chain(readers))
+            },
+            #[cfg(feature = "xml")]
+            FormatFile::Xml => {
+                let record_path = self.record_path.as_deref()
+                    .ok_or_else(|| EtlError::InvalidRecipe("une source xml attend `record_path`".to_string()))?;
+                let readers = paths.iter()
+                    .map(|path| {
+                        let reader = XmlReader::new(path, record_path, self.fields.clone(), self.chunk_size, &self.encoding)?;
+                        Ok(Box::new(reader) as Box<dyn InputPort>)
+                    })
+                    .collect::<EtlResult<Vec<_>>>()?;
+                Ok(self.parallel.chain(readers))
+            },
+            #[cfg(not(feature = "xml"))]
+            FormatFile::Xml => Err(EtlError::InvalidRecipe(
+                "la source xml nécessite la feature `xml`".to_string()
+            )),
+        }
+    }
+
+    #[cfg(feature = "sqlite")]
+    fn open_sqlite(&self, paths: &[&str]) -> EtlResult<Box<dyn InputPort>> {
+        let readers = paths.iter()
+            .map(|path| {
+                let reader = match &self.query {
+                    Some(query) => SqliteReader::from_query(path, query, self.chunk_size)?,
+                    None => SqliteReader::from_table(path, self.table.as_deref().unwrap_or(DEFAULT_TABLE), self.chunk_size)?,
+                };
+                Ok(Box::new(reader) as Box<dyn InputPort>)
+            })
+            .collect::<EtlResult<Vec<_>>>()?;
+
+        Ok(self.parallel.chain(readers))
+    }
+
+    fn open_json(&self, paths: &[&str], layout: JsonLayout) -> EtlResult<Box<dyn InputPort>> {
+        let readers = paths.iter()
+            .map(|path| {
+                let reader = JsonReader::with_encoding(path, layout, self.fields.clone(), self.chunk_size, &self.encoding)?;
+                Ok(Box::new(reader) as Box<dyn InputPort>)
+            })
+            .collect::<EtlResult<Vec<_>>>()?;
+
+        Ok(self.parallel.chain(readers))
+    }
 }
 
 #[derive(Debug, Deserialize)]
 pub struct StepConfig {
     pub action: String,
+    #[serde(default)]
     pub value: String,
+    #[cfg(feature = "script")]
+    #[serde(default)]
+    pub mode: ScriptMode,
+    // Étapes `script` : max_operations, max_string_size, max_array_size, max_map_size
+    #[cfg(feature = "script")]
+    #[serde(flatten)]
+    pub script_limits: ScriptLimits,
+    pub plugin: Option<String>,
+    // Étapes `wasm` : carburant par record et mémoire maximale du plugin
+    #[cfg(feature = "wasm")]
+    pub fuel: Option<u64>,
+    #[cfg(feature = "wasm")]
+    pub memory_limit_mb: Option<usize>,
+    // Étapes `sort` : champ, `direction` (asc, desc) et `nulls` (first, last) de chaque clé.
+    // Étapes `dedup` : seuls les champs comptent
+    #[serde(default)]
+    pub keys: Vec<SortKey>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct OutputConfig {
     pub format: FormatFile,
     pub path: String,
+    // Codec et niveau : none, snappy, gzip, zstd pour parquet ; none, gzip, zstd, bzip2
+    // pour csv/json/ndjson (déduit de l'extension .gz/.zst/.bz2 si absent)
+    pub compression: Option<String>,
+    pub compression_level: Option<i32>,
+    // Renomme les colonnes écrites (même ordre que les champs du record)
+    pub columns: Option<Vec<String>>,
+    // Sorties Excel : nom de la feuille, suffixée `(2)`, `(3)`... au-delà de la limite de lignes
+    pub sheet: Option<String>,
 }
 
+impl OutputConfig {
+    // Choisit l'adapter d'écriture selon `format`
+    pub fn open(&self, chunk_size: usize) -> EtlResult<Box<dyn OutputPort<User>>> {
+        match self.format {
+            #[cfg(feature = "sqlite")]
+            FormatFile::Sqlite => Ok(Box::new(SqliteAdapter::new(&self.path)?)),
+            _ => self.open_records(chunk_size),
+        }
+    }
+
+    // Sorties tabulaires, pour tout `Record` ; la sortie SQLite n'écrit que des `User`
+    #[cfg_attr(not(feature = "parquet"), allow(unused_variables))]
+    pub fn open_records<T: Record + 'static>(&self, chunk_size: usize) -> EtlResult<Box<dyn OutputPort<T>>> {
+        if self.path == STDIO_PATH && !self.format.is_text() {
+            return Err(EtlError::InvalidRecipe(
+                format!("stdout (`-`) n'est pas supporté pour le format {:?}", self.format)
+            ));
+        }
+
+        match self.format {
+            FormatFile::Csv => {
+                let mut adapter = CsvAdapter::new(&self.path, self.text_compression()?, self.compression_level)?;
+                if let Some(columns) = &self.columns {
+                    adapter = adapter.with_columns(columns.clone())?;
+                }
+                Ok(Box::new(adapter))
+            },
+            FormatFile::Json | FormatFile::Ndjson => {
+                let layout = if self.format == FormatFile::Json { JsonLayout::Array } else { JsonLayout::Lines };
+                let mut adapter = JsonAdapter::new(&self.path, layout, self.text_compression()?, self.compression_level)?;
+                if let Some(columns) = &self.columns {
+                    adapter = adapter.with_columns(columns.clone())?;
+                }
+                Ok(Box::new(adapter))
+            },
+            FormatFile::Sqlite => Err(EtlError::InvalidRecipe(
+                "la sortie sqlite n'accepte que des utilisateurs".to_string()
+            )),
+            #[cfg(feature = "parquet")]
+            FormatFile::Parquet => {
+                let mut options = ParquetOptions { row_group_size: chunk_size, ..ParquetOptions::default() };
+                if let Some(codec) = &self.compression {
+                    options = options.with_compression(codec, self.compression_level)?;
+                }
+                let adapter = match &self.columns {
+                    Some(columns) => ParquetAdapter::with_columns(&self.path, columns.clone(), options)?,
+                    None => ParquetAdapter::new(&self.path, options)?,
+                };
+                Ok(Box::new(adapter))
+            },
+            #[cfg(feature = "xlsx")]
+            FormatFile::Xlsx => {
+                let mut adapter = XlsxAdapter::new(&self.path);
+                if let Some(columns) = &self.columns {
+                    adapter = adapter.with_columns(columns.clone())?;
+                }
+                if let Some(sheet) = &self.sheet {
+                    adapter = adapter.with_sheet_name(sheet);
+                }
+                Ok(Box::new(adapter))
+            },
+            // Atteint seulement quand une feature de format est désactivée
+            #[allow(unreachable_patterns)]
+            other => Err(EtlError::InvalidRecipe(
+                format!("format de sortie non supporté: {:?}", other)
+            )),
+        }
+    }
+
+    fn text_compression(&self) -> EtlResult<Compression> {
+        match &self.compression {
+            Some(name) => Compression::from_name(name)
+                .ok_or_else(|| EtlError::InvalidRecipe(format!("compression inconnue `{}`", name))),
+            None => Ok(Compression::from_extension(&self.path)),
+        }
+    }
+}
+
+// Ligne écrite par une étape `aggregate` : valeur du champ et nombre d'utilisateurs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Group {
+    pub key: String,
+    // Gardé en texte pour `Record::field_values`
+    pub count: String,
+}
+
+impl From<(String, usize)> for Group {
+    fn from((key, count): (String, usize)) -> Self {
+        Group { key, count: count.to_string() }
+    }
+}
+
+impl Record for Group {
+    fn field_names() -> Vec<&'static str> {
+        vec!["key", "count"]
+    }
+
+    fn field_values(&self) -> Vec<&str> {
+        vec![&self.key, &self.count]
+    }
+}
+
+// Étapes produisant des `User`, et l'étape `aggregate` qui doit les terminer
+type UserSteps<'s> = (&'s [StepConfig], Option<(usize, &'s StepConfig)>);
+
 impl RecipeConfig {
-    pub fn execute(&self) -> Result<Pipeline<User>, Box<dyn std::error::Error>> {
-        let paths: Vec<&str> = self.source.path.iter().map(|p| p.as_str()).collect();
-        let current_pipeline = multi_extract(&paths)?;
+    fn user_steps(&self) -> EtlResult<UserSteps<'_>> {
+        match self.steps.iter().position(|step| step.action == "aggregate") {
+            None => Ok((&self.steps, None)),
+            Some(index) if index + 1 == self.steps.len() => Ok((&self.steps[..index], Some((index, &self.steps[index])))),
+            Some(index) => Err(EtlError::InvalidRecipe(
+                format!("l'étape {} `aggregate` doit être la dernière", index)
+            )),
+        }
+    }
+
+    fn memory_budget(&self) -> Option<MemoryBudget> {
+        self.memory_budget_mb.map(|mb| MemoryBudget::new(mb * 1024 * 1024))
+    }
+
+    fn first_transform(steps: &[StepConfig]) -> EtlResult<(&StepConfig, TransformFn)> {
+        let first_transform = steps.first()
+            .ok_or_else(|| EtlError::InvalidRecipe("Pas de transformation".to_string()))?;
+        let transform_fn = TransformFn::from_name(first_transform.value.as_str())
+            .ok_or_else(|| unknown_step(0, first_transform))?;
+        Ok((first_transform, transform_fn))
+    }
+
+    // Exécute en mémoire les étapes précédant une éventuelle `aggregate`, appliquée par `run`
+    pub fn execute(&self) -> EtlResult<Pipeline<User>> {
+        let (steps, _) = self.user_steps()?;
+        let files = self.source.files()?;
+        let mut current_pipeline = Pipeline::from_input(self.source.open_files(&files)?)?;
+        current_pipeline.stats.set_sources(files);
+        if let Some(budget) = self.memory_budget() {
+            current_pipeline = current_pipeline.with_memory_budget(budget);
+        }
+
+        let (first_transform, transform_fn) = RecipeConfig::first_transform(steps)?;
+
+        // Les étapes, `generate_user` compris, s'accumulent dans un plan exécuté
+        // en une passe par `collect`, ou plus tôt par une barrière (tri, script `chunk`...)
+        let user_pipeline = transform_fn.apply_to_csv(current_pipeline.lazy())
+            .map_err(|err| err.in_step(0, &first_transform.action))?;
+
+        execute_steps(&steps[1..], 1, user_pipeline)
+    }
+
+    // Exécute la recette puis charge le résultat dans la sortie configurée.
+    // Avec `memory_budget_mb`, les records passent chunk par chunk de la source à la sortie.
+    pub fn run(&self) -> EtlResult<PipelineStats> {
+        if let Some(budget) = self.memory_budget() {
+            return self.run_streaming(budget);
+        }
+
+        let (_, aggregate) = self.user_steps()?;
+        let mut pipeline = self.execute()?;
+        let chunk_size = self.source.chunk_size.max(1);
+
+        match aggregate {
+            None => {
+                write_all(self.output.open(chunk_size)?, &pipeline.data, chunk_size)?;
+                Ok(pipeline.stats)
+            },
+            Some((index, step)) => {
+                let field = user_field(step).map_err(|err| err.in_step(index, &step.action))?;
+                let mut stats = std::mem::take(&mut pipeline.stats);
+                let mut counts: Vec<(String, usize)> = pipeline
+                    .aggregate(|user| user.sort_field(field).to_string())?
+                    .into_iter()
+                    .collect();
+                counts.sort();
+
+                let groups: Vec<Group> = counts.into_iter().map(Group::from).collect();
+                write_all(self.output.open_records(chunk_size)?, &groups, chunk_size)?;
+                stats.total_filtered = groups.len();
+                Ok(stats)
+            },
+        }
+    }
 
-        let first_transform = self.steps.first().ok_or("Pas de transformation")?;
-        let transform_fn = TransformFn::from_str(first_transform.value.as_str())
-            .ok_or_else(|| format!("Transformation inconnue: {}", first_transform.value))?;
+    fn run_streaming(&self, budget: MemoryBudget) -> EtlResult<PipelineStats> {
+        let (steps, aggregate) = self.user_steps()?;
+        let files = self.source.files()?;
+        let mut source = StreamingPipeline::from_input(self.source.open_files(&files)?);
+        source.stats.set_sources(files);
 
-        let mut user_pipeline = transform_fn.apply_to_csv(current_pipeline);
+        let (first_transform, transform_fn) = RecipeConfig::first_transform(steps)?;
+        let mut users = transform_fn.apply_to_csv_stream(source)
+            .map_err(|err| err.in_step(0, &first_transform.action))?;
 
-        for step in self.steps.iter().skip(1) {
-            user_pipeline = execute_step(step, user_pipeline);
+        for (index, step) in steps.iter().enumerate().skip(1) {
+            users = stream_step(index, step, users, &budget)
+                .map_err(|err| step_error(index, step, err))?;
         }
 
-        Ok(user_pipeline)
+        let chunk_size = self.source.chunk_size.max(1);
+        match aggregate {
+            None => {
+                let mut output = self.output.open(chunk_size)?;
+                let stats = users.load(|chunk| output.write(chunk))?;
+                output.finalize()?;
+                Ok(stats)
+            },
+            Some((index, step)) => {
+                let field = user_field(step).map_err(|err| err.in_step(index, &step.action))?;
+                let mut output = self.output.open_records::<Group>(chunk_size)?;
+                let stats = users
+                    .aggregate(budget, move |user| user.sort_field(field).to_string())
+                    .transform(Group::from)
+                    .load(|chunk| output.write(chunk))?;
+                output.finalize()?;
+                Ok(stats)
+            },
+        }
+    }
+}
+
+fn write_all<T>(mut output: Box<dyn OutputPort<T>>, data: &[T], chunk_size: usize) -> EtlResult<()> {
+    for chunk in data.chunks(chunk_size) {
+        output.write(chunk)?;
+    }
+    output.finalize()
+}
+
+fn unknown_step(index: usize, step: &StepConfig) -> EtlError {
+    EtlError::UnknownStep {
+        index,
+        action: step.action.clone(),
+        value: step.value.clone(),
+    }
+}
+
+fn step_error(index: usize, step: &StepConfig, err: EtlError) -> EtlError {
+    match err {
+        EtlError::UnknownStep { .. } => err,
+        err => err.in_step(index, &step.action),
+    }
+}
+
+// Champ de `User` compté par une étape `aggregate`
+fn user_field(step: &StepConfig) -> EtlResult<usize> {
+    User::field_names()
+        .iter()
+        .position(|name| *name == step.value)
+        .ok_or_else(|| EtlError::InvalidRecipe(format!("champ d'agrégation `{}` inconnu", step.value)))
+}
+
+#[cfg(feature = "wasm")]
+fn wasm_plugin(step: &StepConfig) -> EtlResult<TransformFn> {
+    let path = step.plugin.as_deref()
+        .ok_or_else(|| EtlError::InvalidRecipe("L'étape wasm nécessite `plugin`".to_string()))?;
+
+    TransformFn::from_plugin(
+        path,
+        step.fuel.unwrap_or(DEFAULT_FUEL),
+        step.memory_limit_mb.map_or(DEFAULT_MEMORY_LIMIT, |mb| mb * 1024 * 1024)
+    )
+}
+
+// Ajoute les étapes au plan ; après une barrière, la suite part d'un plan sur `User`
+fn execute_steps<'a, S: Send + 'a>(
+    steps: &[StepConfig],
+    first_index: usize,
+    mut pipeline: LazyPipeline<'a, S, User>
+) -> EtlResult<Pipeline<User>> {
+    for (offset, step) in steps.iter().enumerate() {
+        let index = first_index + offset;
+        match execute_step(index, step, pipeline).map_err(|err| step_error(index, step, err))? {
+            Planned::Fused(next) => pipeline = next,
+            Planned::Collected(next) => return execute_steps(&steps[offset + 1..], index + 1, next),
+        }
     }
 
+    Ok(pipeline.collect())
+}
 
+fn execute_step<'a, S: Send + 'a>(
+    index: usize,
+    step: &StepConfig,
+    pipeline: LazyPipeline<'a, S, User>
+) -> EtlResult<Planned<'a, S, User>> {
+    match step.action.as_str() {
+        "transform" => {
+            TransformFn::from_name(&step.value)
+                .ok_or_else(|| unknown_step(index, step))?
+                .apply_to_user(pipeline)
+                .map(Planned::Fused)
+        },
+        "filter" => {
+            FilterFn::from_name(&step.value)
+                .ok_or_else(|| unknown_step(index, step))?
+                .apply_to_user(pipeline)
+                .map(Planned::Fused)
+        },
+        // Barrières : le plan en cours est exécuté avant de trier
+        "sort" => {
+            let order = SortOrder::for_record::<User>(&step.keys)?;
+            Ok(Planned::Collected(pipeline.sort_by(move |a, b| order.compare(a, b))?.lazy()))
+        },
+        "dedup" => {
+            let order = SortOrder::for_record::<User>(&step.keys)?;
+            Ok(Planned::Collected(pipeline.collect().dedup_by(move |a, b| order.compare(a, b))?.lazy()))
+        },
+        #[cfg(feature = "wasm")]
+        "wasm" => wasm_plugin(step)?.apply_to_user(pipeline).map(Planned::Fused),
+        #[cfg(feature = "script")]
+        "script" => {
+            Ok(ScriptFn::from_step(&step.value, step.mode)?.with_limits(step.script_limits).apply_to_user(pipeline))
+        },
+        #[cfg(not(feature = "wasm"))]
+        "wasm" => Err(EtlError::InvalidRecipe("L'étape wasm nécessite la feature `wasm`".to_string())),
+        #[cfg(not(feature = "script"))]
+        "script" => Err(EtlError::InvalidRecipe("L'étape script nécessite la feature `script`".to_string())),
+        _ => Err(unknown_step(index, step))
+    }
 }
 
-fn execute_step(step: &StepConfig, pipeline: Pipeline<User>) -> Pipeline<User> {
+// Comme `execute_step`, sur le flux : `sort` et `dedup` y respectent le budget mémoire
+fn stream_step(
+    index: usize,
+    step: &StepConfig,
+    pipeline: StreamingPipeline<BoxedChunks<User>, User>,
+    budget: &MemoryBudget
+) -> EtlResult<StreamingPipeline<BoxedChunks<User>, User>> {
     match step.action.as_str() {
         "transform" => {
-            if let Some(t) = TransformFn::from_str(&step.value) {
-                t.apply_to_user(pipeline)
-            } else {
-                pipeline
-            }
+            TransformFn::from_name(&step.value)
+                .ok_or_else(|| unknown_step(index, step))?
+                .apply_to_user_stream(pipeline)
         },
         "filter" => {
-            if let Some(f) = FilterFn::from_str(&step.value) {
-                f.apply_to_user(pipeline)
-            } else {
-                pipeline
-            }
+            FilterFn::from_name(&step.value)
+                .ok_or_else(|| unknown_step(index, step))?
+                .apply_to_user_stream(pipeline)
+        },
+        "sort" => {
+            let order = SortOrder::for_record::<User>(&step.keys)?;
+            Ok(pipeline.sort_by(budget.clone(), move |a, b| order.compare(a, b)).boxed())
         },
-        _ => pipeline
+        "dedup" => {
+            let order = SortOrder::for_record::<User>(&step.keys)?;
+            Ok(pipeline.dedup_by(budget.clone(), move |a, b| order.compare(a, b)).boxed())
+        },
+        #[cfg(feature = "wasm")]
+        "wasm" => wasm_plugin(step)?.apply_to_user_stream(pipeline),
+        #[cfg(feature = "script")]
+        "script" => {
+            Ok(ScriptFn::from_step(&step.value, step.mode)?.with_limits(step.script_limits).apply_to_user_stream(pipeline))
+        },
+        #[cfg(not(feature = "wasm"))]
+        "wasm" => Err(EtlError::InvalidRecipe("L'étape wasm nécessite la feature `wasm`".to_string())),
+        #[cfg(not(feature = "script"))]
+        "script" => Err(EtlError::InvalidRecipe("L'étape script nécessite la feature `script`".to_string())),
+        _ => Err(unknown_step(index, step))
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::parse_yaml::parse_yaml;
+
+    fn recipe(steps: &str) -> RecipeConfig {
+        serde_yaml::from_str(&format!(r#"
+name: "test"
+source:
+    format: "csv"
+    path: ["./src/data/data_4.csv"]
+steps:
+{}
+output:
+    format: "sqlite"
+    path: "./output_test.db"
+"#, steps)).unwrap()
+    }
+
+    #[test]
+    fn test_missing_recipe_file_is_an_error() {
+        let err = parse_yaml("./recipes/does_not_exist.yaml").unwrap_err();
+        assert!(matches!(err, EtlError::Io { .. }));
+    }
+
+    #[test]
+    fn test_unknown_step_is_an_error() {
+        let recipe = recipe(r#"
+    - action: "transform"
+      value: "generate_user"
+    - action: "filter"
+      value: "does_not_exist"
+"#);
+
+        let err = recipe.execute().err().unwrap();
+        assert!(matches!(err, EtlError::UnknownStep { index: 1, .. }));
+    }
+
+    #[test]
+    fn test_unsupported_step_has_context() {
+        let recipe = recipe(r#"
+    - action: "transform"
+      value: "generate_user"
+    - action: "transform"
+      value: "generate_user"
+"#);
+
+        let err = recipe.execute().err().unwrap();
+        assert!(matches!(err, EtlError::Step { index: 1, .. }));
+        assert!(std::error::Error::source(&err).is_some());
+    }
+
+    #[test]
+    fn test_sort_step_orders_users() {
+        let sorted = recipe(r#"
+    - action: "transform"
+      value: "generate_user"
+    - action: "sort"
+      keys:
+        - {field: "last_name", direction: "desc"}
+        - {field: "username"}
+"#);
+
+        let users = sorted.execute().unwrap().data;
+        assert!(users.len() > 1);
+        assert!(users.windows(2).all(|pair| {
+            let (a, b) = (&pair[0], &pair[1]);
+            a.last_name > b.last_name || (a.last_name == b.last_name && a.username <= b.username) || b.last_name.is_empty()
+        }));
+
+        let unknown_field = recipe(r#"
+    - action: "transform"
+      value: "generate_user"
+    - action: "sort"
+      keys: [{field: "age"}]
+"#);
+        assert!(matches!(unknown_field.execute().err().unwrap(), EtlError::Step { index: 1, .. }));
+    }
+
+    #[test]
+    fn test_streaming_run_matches_batch_run() {
+        let run = |budget: &str, steps: &str, output: &str| {
+            let path = std::env::temp_dir().join(output);
+            let recipe: RecipeConfig = serde_yaml::from_str(&format!(r#"
+name: "budget"
+source:
+    format: "csv"
+    path: ["./src/data/data_4.csv"]
+    chunk_size: 100
+steps:
+    - action: "transform"
+      value: "generate_user"
+{}
+output:
+    format: "csv"
+    path: "{}"
+{}
+"#, steps, path.to_str().unwrap(), budget)).unwrap();
+            let stats = recipe.run().unwrap();
+            (stats.total_filtered, std::fs::read_to_string(path).unwrap())
+        };
+
+        let dedup = r#"
+    - action: "dedup"
+      keys: [{field: "last_name"}]"#;
+        let aggregate = r#"
+    - action: "aggregate"
+      value: "first_name""#;
+
+        let (kept, batch) = run("", dedup, "recipe_dedup_batch.csv");
+        assert!(kept > 1);
+        assert_eq!(run("memory_budget_mb: 1", dedup, "recipe_dedup_stream.csv"), (kept, batch));
+
+        let (groups, batch) = run("", aggregate, "recipe_aggregate_batch.csv");
+        assert!(batch.starts_with("key,count\n"));
+        assert_eq!(run("memory_budget_mb: 1", aggregate, "recipe_aggregate_stream.csv"), (groups, batch));
+
+        let misplaced = recipe(r#"
+    - action: "transform"
+      value: "generate_user"
+    - action: "aggregate"
+      value: "first_name"
+    - action: "filter"
+      value: "is_valid"
+"#);
+        assert!(matches!(misplaced.execute().err().unwrap(), EtlError::InvalidRecipe(_)));
+    }
+
+    #[test]
+    fn test_glob_source_records_resolved_files() {
+        let source: SourceConfig = serde_yaml::from_str(r#"
+format: "csv"
+path: ["./src/data/data_5.csv", "./src/data/*.csv"]
+exclude: ["data_4.csv"]
+"#).unwrap();
+
+        assert_eq!(source.files().unwrap(), vec!["./src/data/data_5.csv", "./src/data/data_1.csv"]);
+    }
+
+    #[test]
+    fn test_stdout_only_for_text_formats() {
+        let output: OutputConfig = serde_yaml::from_str(r#"{format: "parquet", path: "-"}"#).unwrap();
+        assert!(matches!(output.open(100).err().unwrap(), EtlError::InvalidRecipe(_)));
+    }
+
+    #[test]
+    fn test_csv_dialect_options() {
+        let source: SourceConfig = serde_yaml::from_str(r##"
+format: "csv"
+path: ["./src/data/data_4.csv"]
+delimiter: ";"
+comment: "#"
+flexible: true
+"##).unwrap();
+
+        assert_eq!(source.dialect.delimiter, Some(';'));
+        assert_eq!(source.dialect.comment, Some('#'));
+        assert_eq!(source.dialect.quote, '"');
+        assert!(source.dialect.flexible && !source.dialect.auto_detect);
+    }
+
+    #[test]
+    fn test_fixed_width_source_feeds_user_steps() {
+        let path = std::env::temp_dir().join("recipe_fixed_width.txt");
+        std::fs::write(&path, "jdupont   0001Jean      Dupont\nmmartin   0002Marie     Martin\n").unwrap();
+
+        let recipe: RecipeConfig = serde_yaml::from_str(&format!(r#"
+name: "fixed"
+source:
+    format: "fixed_width"
+    path: ["{}"]
+    columns:
+        - {{name: "username", start: 0, width: 10}}
+        - {{name: "identifier", start: 10, width: 4}}
+        - {{name: "first_name", start: 14, width: 10}}
+        - {{name: "last_name", start: 24, width: 10}}
+steps:
+    - action: "transform"
+      value: "generate_user"
+output:
+    format: "csv"
+    path: "./output_test.csv"
+"#, path.to_str().unwrap())).unwrap();
+
+        let pipeline = recipe.execute().unwrap();
+        assert_eq!(pipeline.data.len(), 2);
+        assert_eq!(pipeline.data[1].last_name, "Martin");
+    }
+}
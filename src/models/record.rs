@@ -0,0 +1,6 @@
+// Vue tabulaire d'un record, utilisée par les adapters de sortie orientés
+// colonnes : noms et valeurs sont donnés dans le même ordre.
+pub trait Record {
+    fn field_names() -> Vec<&'static str>;
+    fn field_values(&self) -> Vec<&str>;
+}
@@ -1,13 +1,20 @@
-use serde::de::Error;
-use crate::models::pipeline::Pipeline;
+use crate::models::error::{EtlError, EtlResult};
+use crate::models::lazy_pipeline::LazyPipeline;
+use crate::models::stream_pipeline::{BoxedChunks, StreamingPipeline};
 use crate::models::user::User;
+#[cfg(feature = "wasm")]
+use crate::models::wasm_plugin::WasmPlugin;
 use crate::utils::set_user::generate_user;
 
+type UserFn = Box<dyn Fn(User) -> Result<User, String> + Send + Sync>;
+
 #[derive(Debug)]
 pub enum TransformFn {
     GenerateUser,
     Capitalize,
-    Lowercase
+    Lowercase,
+    #[cfg(feature = "wasm")]
+    Plugin(WasmPlugin)
 }
 
 pub enum FilterFn {
@@ -15,7 +22,7 @@ pub enum FilterFn {
 }
 
 impl TransformFn {
-    pub fn from_str(name: &str) -> Option<TransformFn> {
+    pub fn from_name(name: &str) -> Option<TransformFn> {
         match name {
             "generate_user" => Some(TransformFn::GenerateUser),
             "capitalize" => Some(TransformFn::Capitalize),
@@ -24,46 +31,89 @@ impl TransformFn {
         }
     }
 
-    pub fn apply_to_csv(self, pipeline: Pipeline<csv::StringRecord> ) -> Pipeline<User> {
+    #[cfg(feature = "wasm")]
+    pub fn from_plugin(path: &str, fuel: u64, memory_limit: usize) -> EtlResult<TransformFn> {
+        Ok(TransformFn::Plugin(WasmPlugin::load(path)?.with_limits(fuel, memory_limit)))
+    }
+
+    pub fn apply_to_csv<'a>(self, pipeline: LazyPipeline<'a, csv::StringRecord, csv::StringRecord>)
+    -> EtlResult<LazyPipeline<'a, csv::StringRecord, User>> {
         match self {
-            TransformFn::GenerateUser => pipeline.transform(generate_user),
-            _ => panic!("Cette transformation ne marche que sur GenerateUser")
+            TransformFn::GenerateUser => Ok(pipeline.transform(generate_user)),
+            other => Err(EtlError::UnsupportedStep {
+                name: format!("{:?}", other),
+                reason: "seul generate_user s'applique aux records CSV".to_string(),
+            })
         }
     }
 
-    pub fn apply_to_user(self, pipeline: Pipeline<User>) -> Pipeline<User> {
+    pub fn apply_to_csv_stream<I>(self, pipeline: StreamingPipeline<I, csv::StringRecord>)
+    -> EtlResult<StreamingPipeline<BoxedChunks<User>, User>>
+    where
+        I: Iterator<Item = Vec<csv::StringRecord>> + Send + 'static
+    {
         match self {
-            TransformFn::Capitalize => {
-                pipeline.transform(|mut user| {
-                    user.first_name = user.first_name.to_uppercase();
-                    user
-                })
-            },
-            TransformFn::Lowercase => {
-                pipeline.transform(|mut user| {
-                    user.first_name = user.first_name.to_lowercase();
-                    user
-                })
-            },
-            _ => panic!("Cette transformation ne marche pas sur des Users!")
+            TransformFn::GenerateUser => Ok(pipeline.transform(generate_user).boxed()),
+            other => Err(EtlError::UnsupportedStep {
+                name: format!("{:?}", other),
+                reason: "seul generate_user s'applique aux records CSV".to_string(),
+            })
         }
     }
-    
+
+    // Même transformation pour le plan paresseux et le flux
+    fn user_fn(self) -> EtlResult<UserFn> {
+        match self {
+            TransformFn::Capitalize => Ok(Box::new(|mut user: User| {
+                user.first_name = user.first_name.to_uppercase();
+                Ok(user)
+            })),
+            TransformFn::Lowercase => Ok(Box::new(|mut user: User| {
+                user.first_name = user.first_name.to_lowercase();
+                Ok(user)
+            })),
+            #[cfg(feature = "wasm")]
+            TransformFn::Plugin(plugin) => Ok(Box::new(move |user| plugin.run_on_user(user))),
+            TransformFn::GenerateUser => Err(EtlError::UnsupportedStep {
+                name: "generate_user".to_string(),
+                reason: "ne s'applique qu'aux records CSV".to_string(),
+            })
+        }
+    }
+
+    // Les opérateurs sont seulement ajoutés au plan, exécuté par `collect`
+    pub fn apply_to_user<'a, S: Send + 'a>(self, pipeline: LazyPipeline<'a, S, User>) -> EtlResult<LazyPipeline<'a, S, User>> {
+        Ok(pipeline.try_transform(self.user_fn()?))
+    }
+
+    pub fn apply_to_user_stream(self, pipeline: StreamingPipeline<BoxedChunks<User>, User>)
+    -> EtlResult<StreamingPipeline<BoxedChunks<User>, User>> {
+        Ok(pipeline.try_transform(self.user_fn()?).boxed())
+    }
+
 }
 
 impl FilterFn {
-    pub fn from_str(name: &str) -> Option<FilterFn> {
+    pub fn from_name(name: &str) -> Option<FilterFn> {
         match name {
             "is_valid" => Some(FilterFn::IsValid),
             _ => None
         }
     }
-    pub fn apply_to_user(self, pipeline: Pipeline<User>) -> Pipeline<User> {
+    pub fn apply_to_user<'a, S: Send + 'a>(self, pipeline: LazyPipeline<'a, S, User>) -> EtlResult<LazyPipeline<'a, S, User>> {
+        match self {
+            FilterFn::IsValid => {
+                Ok(pipeline.filter(|user| user.is_valid().is_ok()))
+            },
+        }
+    }
+
+    pub fn apply_to_user_stream(self, pipeline: StreamingPipeline<BoxedChunks<User>, User>)
+    -> EtlResult<StreamingPipeline<BoxedChunks<User>, User>> {
         match self {
             FilterFn::IsValid => {
-                pipeline.filter(|user| user.is_valid().is_ok())
+                Ok(pipeline.filter(|user| user.is_valid().is_ok()).boxed())
             },
-            _ => panic!("Ce filtre ne marche que sur IsValid")
         }
     }
 }
@@ -0,0 +1,255 @@
+use std::fs;
+use rhai::{Array, Dynamic, Engine, Map, Scope, AST};
+use serde::Deserialize;
+use crate::models::error::{EtlError, EtlResult};
+use crate::models::lazy_pipeline::{LazyPipeline, Planned};
+use crate::models::stream_pipeline::{BoxedChunks, StreamingPipeline};
+use crate::models::user::User;
+
+// Taille des paquets passés au script en mode `chunk` sur un `LazyPipeline`
+const DEFAULT_SCRIPT_CHUNK_SIZE: usize = 1000;
+
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ScriptMode {
+    // Le script reçoit la variable `record`
+    #[default]
+    Record,
+    // Le script reçoit la variable `records` (tableau de records)
+    Chunk,
+}
+
+// Bornes du coût d'un script, réglables depuis l'étape de la recette
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(default)]
+pub struct ScriptLimits {
+    pub max_operations: u64,
+    pub max_string_size: usize,
+    pub max_array_size: usize,
+    pub max_map_size: usize,
+}
+
+impl Default for ScriptLimits {
+    fn default() -> Self {
+        ScriptLimits {
+            max_operations: 100_000,
+            max_string_size: 64 * 1024,
+            max_array_size: 100_000,
+            max_map_size: 1_000,
+        }
+    }
+}
+
+pub struct ScriptFn {
+    engine: Engine,
+    ast: AST,
+    mode: ScriptMode,
+}
+
+impl ScriptFn {
+    pub fn new(source: &str, mode: ScriptMode) -> EtlResult<Self> {
+        let engine = sandboxed_engine();
+        let ast = engine.compile(source)
+            .map_err(|err| EtlError::Script { source: Box::new(err) })?;
+
+        Ok(ScriptFn { engine, ast, mode })
+    }
+
+    /// `value` est soit le code du script, soit le chemin d'un fichier `.rhai`.
+    pub fn from_step(value: &str, mode: ScriptMode) -> EtlResult<Self> {
+        if value.trim_end().ends_with(".rhai") {
+            let source = fs::read_to_string(value.trim())
+                .map_err(|err| EtlError::io(value.trim(), err))?;
+            ScriptFn::new(&source, mode)
+        } else {
+            ScriptFn::new(value, mode)
+        }
+    }
+
+    pub fn with_limits(mut self, limits: ScriptLimits) -> Self {
+        apply_limits(&mut self.engine, limits);
+        self
+    }
+
+    pub fn run_on_user(&self, user: User) -> Result<User, String> {
+        let mut scope = Scope::new();
+        scope.push("record", user_to_map(user));
+
+        self.engine
+            .run_ast_with_scope(&mut scope, &self.ast)
+            .map_err(|err| err.to_string())?;
+
+        let record = scope.get_value::<Map>("record")
+            .ok_or("`record` n'est plus un objet après le script")?;
+
+        map_to_user(record)
+    }
+
+    pub fn run_on_chunk(&self, users: Vec<User>) -> Result<Vec<User>, String> {
+        let records: Array = users.into_iter()
+            .map(|user| Dynamic::from_map(user_to_map(user)))
+            .collect();
+
+        let mut scope = Scope::new();
+        scope.push("records", records);
+
+        self.engine
+            .run_ast_with_scope(&mut scope, &self.ast)
+            .map_err(|err| err.to_string())?;
+
+        let records = scope.get_value::<Array>("records")
+            .ok_or("`records` n'est plus un tableau après le script")?;
+
+        records.into_iter()
+            .map(|record| {
+                record.try_cast::<Map>()
+                    .ok_or_else(|| "`records` contient un élément qui n'est pas un objet".to_string())
+                    .and_then(map_to_user)
+            })
+            .collect()
+    }
+
+    // En mode `chunk`, le plan en cours est exécuté avant de passer au script
+    pub fn apply_to_user<'a, S: Send + 'a>(self, pipeline: LazyPipeline<'a, S, User>) -> Planned<'a, S, User> {
+        match self.mode {
+            ScriptMode::Record => Planned::Fused(pipeline.try_transform(move |user| self.run_on_user(user))),
+            ScriptMode::Chunk => Planned::Collected(pipeline.try_transform_chunks(
+                DEFAULT_SCRIPT_CHUNK_SIZE,
+                |users| self.run_on_chunk(users)
+            )),
+        }
+    }
+
+    // En flux, le mode `chunk` reçoit les chunks de la source
+    pub fn apply_to_user_stream(self, pipeline: StreamingPipeline<BoxedChunks<User>, User>) -> StreamingPipeline<BoxedChunks<User>, User> {
+        match self.mode {
+            ScriptMode::Record => pipeline.try_transform(move |user| self.run_on_user(user)).boxed(),
+            ScriptMode::Chunk => pipeline.try_transform_chunks(move |users| self.run_on_chunk(users)).boxed(),
+        }
+    }
+}
+
+fn sandboxed_engine() -> Engine {
+    // Engine::new() n'expose ni fichiers ni réseau : on borne juste le coût
+    let mut engine = Engine::new();
+    engine.set_max_call_levels(32);
+    engine.set_max_expr_depths(64, 32);
+    apply_limits(&mut engine, ScriptLimits::default());
+
+    // stdout peut porter la sortie du pipeline (`-`) : print et debug vont sur stderr
+    engine.on_print(|text| eprintln!("{}", text));
+    engine.on_debug(|text, source, pos| match source {
+        Some(source) => eprintln!("{} @ {:?} | {}", source, pos, text),
+        None => eprintln!("{:?} | {}", pos, text),
+    });
+    engine
+}
+
+fn apply_limits(engine: &mut Engine, limits: ScriptLimits) {
+    engine.set_max_operations(limits.max_operations);
+    engine.set_max_string_size(limits.max_string_size);
+    engine.set_max_array_size(limits.max_array_size);
+    engine.set_max_map_size(limits.max_map_size);
+}
+
+fn user_to_map(user: User) -> Map {
+    let mut map = Map::new();
+    map.insert("username".into(), user.username.into());
+    map.insert("identifier".into(), user.identifier.into());
+    map.insert("first_name".into(), user.first_name.into());
+    map.insert("last_name".into(), user.last_name.into());
+    map
+}
+
+fn map_to_user(mut map: Map) -> Result<User, String> {
+    let mut field = |name: &str| -> Result<String, String> {
+        map.remove(name)
+            .ok_or_else(|| format!("champ `{}` manquant", name))?
+            .into_string()
+            .map_err(|kind| format!("champ `{}` doit être une chaîne (reçu {})", name, kind))
+    };
+
+    Ok(User {
+        username: field("username")?,
+        identifier: field("identifier")?,
+        first_name: field("first_name")?,
+        last_name: field("last_name")?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::pipeline::Pipeline;
+    use crate::models::stream_pipeline::StreamingPipeline;
+    use super::*;
+
+    fn user(username: &str) -> User {
+        User {
+            username: username.to_string(),
+            identifier: "id".to_string(),
+            first_name: "jean".to_string(),
+            last_name: "dupont".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_script_record_and_errors() {
+        let script = ScriptFn::new(
+            r#"
+                if record.username == "boom" { throw "username interdit"; }
+                record.first_name = record.first_name.to_upper();
+            "#,
+            ScriptMode::Record
+        ).unwrap();
+
+        let pipeline = Pipeline {
+            data: vec![user("alice"), user("boom"), user("bob")],
+            ..Default::default()
+        };
+
+        let pipeline = script.apply_to_user(pipeline.lazy()).collect();
+
+        assert_eq!(pipeline.data.len(), 2);
+        assert!(pipeline.data.iter().all(|u| u.first_name == "JEAN"));
+        assert_eq!(pipeline.stats.errors().len(), 1);
+        assert!(pipeline.stats.errors()[0].starts_with("1 record transform error"));
+    }
+
+    #[test]
+    fn test_script_chunk() {
+        let script = ScriptFn::new(
+            "records = records.filter(|r| r.username.len() > 3);",
+            ScriptMode::Chunk
+        ).unwrap();
+
+        let users = script.run_on_chunk(vec![user("alice"), user("bob")]).unwrap();
+
+        assert_eq!(users.len(), 1);
+        assert_eq!(users[0].username, "alice");
+    }
+
+    #[test]
+    fn test_script_limits() {
+        let script = ScriptFn::new("for i in 0..1000 { record.username += \"x\"; }", ScriptMode::Record)
+            .unwrap();
+
+        assert!(script.run_on_user(user("alice")).is_ok());
+
+        let script = script.with_limits(ScriptLimits { max_operations: 100, ..Default::default() });
+        assert!(script.run_on_user(user("alice")).is_err());
+    }
+
+    #[test]
+    fn test_script_streaming() -> EtlResult<()> {
+        let script = ScriptFn::new("record.last_name = 42;", ScriptMode::Record)?;
+
+        let stats = StreamingPipeline::new(vec![vec![user("alice"), user("bob")]].into_iter())
+            .try_transform(|user| script.run_on_user(user))
+            .load(|_| Ok(()))?;
+
+        assert_eq!(stats.total_filtered, 0);
+        assert_eq!(stats.errors().len(), 2);
+
+        Ok(())
+    }
+}
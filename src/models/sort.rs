@@ -0,0 +1,139 @@
+use std::cmp::Ordering;
+use serde::Deserialize;
+use crate::models::error::{EtlError, EtlResult};
+use crate::models::record::Record;
+use crate::models::user::User;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortDirection {
+    #[default]
+    Asc,
+    Desc,
+}
+
+// Place des valeurs vides, quel que soit le sens du tri
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NullOrder {
+    First,
+    #[default]
+    Last,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SortKey {
+    pub field: String,
+    #[serde(default)]
+    pub direction: SortDirection,
+    #[serde(default)]
+    pub nulls: NullOrder,
+}
+
+impl SortKey {
+    pub fn asc(field: &str) -> Self {
+        SortKey { field: field.to_string(), direction: SortDirection::Asc, nulls: NullOrder::Last }
+    }
+
+    pub fn desc(field: &str) -> Self {
+        SortKey { field: field.to_string(), direction: SortDirection::Desc, nulls: NullOrder::Last }
+    }
+
+    pub fn nulls_first(mut self) -> Self {
+        self.nulls = NullOrder::First;
+        self
+    }
+}
+
+// Accès à un champ par sa position, sans allouer : les comparaisons d'un tri sont nombreuses
+pub trait SortFields {
+    fn sort_field(&self, index: usize) -> &str;
+}
+
+impl SortFields for csv::StringRecord {
+    fn sort_field(&self, index: usize) -> &str {
+        self.get(index).unwrap_or_default()
+    }
+}
+
+impl SortFields for User {
+    fn sort_field(&self, index: usize) -> &str {
+        match index {
+            0 => &self.username,
+            1 => &self.identifier,
+            2 => &self.first_name,
+            _ => &self.last_name,
+        }
+    }
+}
+
+// Clés de tri résolues en positions de champs. Les valeurs sont comparées
+// comme des chaînes ; un champ vide ou absent est une valeur nulle.
+#[derive(Debug, Clone)]
+pub struct SortOrder {
+    keys: Vec<(usize, SortKey)>,
+}
+
+impl SortOrder {
+    pub fn new(keys: &[SortKey], names: &[&str]) -> EtlResult<Self> {
+        if keys.is_empty() {
+            return Err(EtlError::InvalidRecipe("le tri attend au moins une clé".to_string()));
+        }
+
+        let keys = keys.iter()
+            .map(|key| {
+                names.iter()
+                    .position(|name| *name == key.field)
+                    .map(|index| (index, key.clone()))
+                    .ok_or_else(|| EtlError::InvalidRecipe(format!("champ de tri `{}` inconnu", key.field)))
+            })
+            .collect::<EtlResult<Vec<_>>>()?;
+        Ok(SortOrder { keys })
+    }
+
+    pub fn for_record<R: Record>(keys: &[SortKey]) -> EtlResult<Self> {
+        SortOrder::new(keys, &R::field_names())
+    }
+
+    pub fn compare<T: SortFields>(&self, a: &T, b: &T) -> Ordering {
+        for (index, key) in &self.keys {
+            let (a, b) = (a.sort_field(*index), b.sort_field(*index));
+            let ordering = match (a.is_empty(), b.is_empty()) {
+                (true, true) => Ordering::Equal,
+                (true, false) if key.nulls == NullOrder::First => Ordering::Less,
+                (true, false) => Ordering::Greater,
+                (false, true) if key.nulls == NullOrder::First => Ordering::Greater,
+                (false, true) => Ordering::Less,
+                (false, false) if key.direction == SortDirection::Desc => b.cmp(a),
+                (false, false) => a.cmp(b),
+            };
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+        }
+        Ordering::Equal
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_multi_key_order_with_nulls() {
+        let order = SortOrder::new(
+            &[SortKey::desc("ville").nulls_first(), SortKey::asc("login")],
+            &["login", "ville"],
+        ).unwrap();
+
+        let mut records: Vec<csv::StringRecord> = [["b", "Lyon"], ["a", "Paris"], ["c", ""], ["a", "Lyon"], ["", "Lyon"]]
+            .into_iter()
+            .map(|fields| csv::StringRecord::from(fields.to_vec()))
+            .collect();
+        records.sort_by(|a, b| order.compare(a, b));
+
+        let logins: Vec<&str> = records.iter().map(|r| &r[0]).collect();
+        assert_eq!(logins, ["c", "a", "a", "b", ""]);
+        assert!(SortOrder::new(&[SortKey::asc("age")], &["login"]).is_err());
+    }
+}
@@ -0,0 +1,479 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fs::File;
+use std::hash::Hash;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::iter::Peekable;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+use rayon::prelude::*;
+use crate::models::error::{EtlError, EtlResult};
+use crate::models::pipeline::PipelineStats;
+use crate::models::user::User;
+
+static SPILL_FILES: AtomicUsize = AtomicUsize::new(0);
+
+// Mémoire que peuvent occuper les données d'un opérateur avant d'être
+// déversées, triées, dans des fichiers temporaires de `dir`.
+#[derive(Debug, Clone)]
+pub struct MemoryBudget {
+    pub max_bytes: usize,
+    pub dir: PathBuf,
+}
+
+impl MemoryBudget {
+    pub fn new(max_bytes: usize) -> Self {
+        MemoryBudget { max_bytes, dir: std::env::temp_dir() }
+    }
+
+    pub fn with_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.dir = dir.into();
+        self
+    }
+}
+
+// Record pouvant être écrit dans un fichier de débordement puis relu.
+// `estimated_size` compte la structure et ses allocations.
+pub trait Spill: Sized {
+    fn estimated_size(&self) -> usize;
+    fn write_spill(&self, out: &mut dyn Write) -> io::Result<()>;
+    /// `Ok(None)` en fin de fichier.
+    fn read_spill(input: &mut dyn Read) -> io::Result<Option<Self>>;
+}
+
+fn write_len(out: &mut dyn Write, len: usize) -> io::Result<()> {
+    out.write_all(&(len as u64).to_le_bytes())
+}
+
+fn read_len(input: &mut dyn Read) -> io::Result<Option<usize>> {
+    let mut bytes = [0; 8];
+    match input.read_exact(&mut bytes) {
+        Ok(()) => Ok(Some(u64::from_le_bytes(bytes) as usize)),
+        Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+fn write_str(out: &mut dyn Write, value: &str) -> io::Result<()> {
+    write_len(out, value.len())?;
+    out.write_all(value.as_bytes())
+}
+
+// Champ au milieu d'un record : la fin de fichier est une erreur
+fn read_string(input: &mut dyn Read) -> io::Result<String> {
+    Ok(String::read_spill(input)?.ok_or(io::ErrorKind::UnexpectedEof)?)
+}
+
+impl Spill for String {
+    fn estimated_size(&self) -> usize {
+        size_of::<String>() + self.len()
+    }
+
+    fn write_spill(&self, out: &mut dyn Write) -> io::Result<()> {
+        write_str(out, self)
+    }
+
+    fn read_spill(input: &mut dyn Read) -> io::Result<Option<Self>> {
+        let Some(len) = read_len(input)? else { return Ok(None) };
+        let mut bytes = vec![0; len];
+        input.read_exact(&mut bytes)?;
+        String::from_utf8(bytes).map(Some).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+}
+
+impl Spill for csv::StringRecord {
+    fn estimated_size(&self) -> usize {
+        size_of::<csv::StringRecord>() + self.as_slice().len() + self.len() * size_of::<usize>()
+    }
+
+    fn write_spill(&self, out: &mut dyn Write) -> io::Result<()> {
+        write_len(out, self.len())?;
+        self.iter().try_for_each(|field| write_str(out, field))
+    }
+
+    fn read_spill(input: &mut dyn Read) -> io::Result<Option<Self>> {
+        let Some(len) = read_len(input)? else { return Ok(None) };
+        let mut record = csv::StringRecord::with_capacity(0, len);
+        for _ in 0..len {
+            record.push_field(&read_string(input)?);
+        }
+        Ok(Some(record))
+    }
+}
+
+impl Spill for User {
+    fn estimated_size(&self) -> usize {
+        size_of::<User>() + self.username.len() + self.identifier.len() + self.first_name.len() + self.last_name.len()
+    }
+
+    fn write_spill(&self, out: &mut dyn Write) -> io::Result<()> {
+        [&self.username, &self.identifier, &self.first_name, &self.last_name]
+            .into_iter()
+            .try_for_each(|field| write_str(out, field))
+    }
+
+    fn read_spill(input: &mut dyn Read) -> io::Result<Option<Self>> {
+        let Some(username) = String::read_spill(input)? else { return Ok(None) };
+        Ok(Some(User {
+            username,
+            identifier: read_string(input)?,
+            first_name: read_string(input)?,
+            last_name: read_string(input)?,
+        }))
+    }
+}
+
+// Record accompagné de sa position d'origine, pour revenir à l'ordre initial
+pub(crate) struct Indexed<T>(pub usize, pub T);
+
+impl<T: Spill> Spill for Indexed<T> {
+    fn estimated_size(&self) -> usize {
+        size_of::<usize>() + self.1.estimated_size()
+    }
+
+    fn write_spill(&self, out: &mut dyn Write) -> io::Result<()> {
+        write_len(out, self.0)?;
+        self.1.write_spill(out)
+    }
+
+    fn read_spill(input: &mut dyn Read) -> io::Result<Option<Self>> {
+        let Some(index) = read_len(input)? else { return Ok(None) };
+        let item = T::read_spill(input)?.ok_or(io::ErrorKind::UnexpectedEof)?;
+        Ok(Some(Indexed(index, item)))
+    }
+}
+
+// Clé d'agrégation et son compte partiel
+pub(crate) struct Counted<K>(pub K, pub usize);
+
+impl<K: Spill> Spill for Counted<K> {
+    fn estimated_size(&self) -> usize {
+        self.0.estimated_size() + size_of::<usize>()
+    }
+
+    fn write_spill(&self, out: &mut dyn Write) -> io::Result<()> {
+        self.0.write_spill(out)?;
+        write_len(out, self.1)
+    }
+
+    fn read_spill(input: &mut dyn Read) -> io::Result<Option<Self>> {
+        let Some(key) = K::read_spill(input)? else { return Ok(None) };
+        let count = read_len(input)?.ok_or(io::ErrorKind::UnexpectedEof)?;
+        Ok(Some(Counted(key, count)))
+    }
+}
+
+// Fichier temporaire supprimé quand il n'est plus référencé
+struct SpillFile {
+    path: PathBuf,
+    bytes: u64,
+}
+
+impl SpillFile {
+    fn write<T: Spill>(dir: &std::path::Path, items: &[T]) -> EtlResult<Self> {
+        let id = SPILL_FILES.fetch_add(1, AtomicOrdering::Relaxed);
+        let path = dir.join(format!("etl-spill-{}-{}.run", std::process::id(), id));
+        let io_error = |err| EtlError::io(&path.to_string_lossy(), err);
+
+        let mut out = BufWriter::new(File::create(&path).map_err(io_error)?);
+        // Créé avant l'écriture pour que le fichier soit supprimé en cas d'erreur
+        let mut file = SpillFile { path: path.clone(), bytes: 0 };
+        items.iter().try_for_each(|item| item.write_spill(&mut out)).map_err(io_error)?;
+        let written = out.into_inner().map_err(|err| io_error(err.into_error()))?;
+        file.bytes = written.metadata().map_err(io_error)?.len();
+        Ok(file)
+    }
+
+    fn path(&self) -> String {
+        self.path.to_string_lossy().into_owned()
+    }
+}
+
+impl Drop for SpillFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+struct Run<T> {
+    file: SpillFile,
+    reader: BufReader<File>,
+    head: Option<T>,
+}
+
+impl<T: Spill> Run<T> {
+    fn open(file: SpillFile) -> EtlResult<Self> {
+        let reader = File::open(&file.path).map_err(|err| EtlError::io(&file.path(), err))?;
+        let mut run = Run { file, reader: BufReader::new(reader), head: None };
+        run.advance()?;
+        Ok(run)
+    }
+
+    fn advance(&mut self) -> EtlResult<Option<T>> {
+        let next = T::read_spill(&mut self.reader).map_err(|err| EtlError::io(&self.file.path(), err))?;
+        Ok(std::mem::replace(&mut self.head, next))
+    }
+}
+
+// Tri externe : les records sont gardés en mémoire jusqu'au budget, puis
+// triés en parallèle et écrits dans une série triée ; `finish` fusionne les séries.
+pub struct ExternalSorter<T, F> {
+    budget: MemoryBudget,
+    compare: F,
+    buffer: Vec<T>,
+    buffered_bytes: usize,
+    runs: Vec<SpillFile>,
+}
+
+impl<T, F> ExternalSorter<T, F>
+where
+    T: Spill + Send,
+    F: Fn(&T, &T) -> Ordering + Sync
+{
+    pub fn new(budget: MemoryBudget, compare: F) -> Self {
+        ExternalSorter { budget, compare, buffer: Vec::new(), buffered_bytes: 0, runs: Vec::new() }
+    }
+
+    pub fn push(&mut self, item: T) -> EtlResult<()> {
+        self.buffered_bytes += item.estimated_size();
+        self.buffer.push(item);
+        if self.buffered_bytes > self.budget.max_bytes {
+            self.spill()?;
+        }
+        Ok(())
+    }
+
+    fn spill(&mut self) -> EtlResult<()> {
+        // Tri stable : à clé égale, l'ordre d'arrivée est conservé
+        self.buffer.par_sort_by(&self.compare);
+        self.runs.push(SpillFile::write(&self.budget.dir, &self.buffer)?);
+        self.buffer.clear();
+        self.buffer.shrink_to_fit();
+        self.buffered_bytes = 0;
+        Ok(())
+    }
+
+    pub fn spilled_runs(&self) -> usize {
+        self.runs.len()
+    }
+
+    pub fn spilled_bytes(&self) -> u64 {
+        self.runs.iter().map(|run| run.bytes).sum()
+    }
+
+    pub fn finish(mut self) -> EtlResult<Sorted<T, F>> {
+        if self.runs.is_empty() {
+            self.buffer.par_sort_by(&self.compare);
+            return Ok(Sorted { memory: self.buffer.into_iter(), runs: Vec::new(), compare: self.compare });
+        }
+
+        if !self.buffer.is_empty() {
+            self.spill()?;
+        }
+        let runs = self.runs.into_iter()
+            .map(Run::open)
+            .collect::<EtlResult<Vec<_>>>()?;
+        Ok(Sorted { memory: Vec::new().into_iter(), runs, compare: self.compare })
+    }
+}
+
+// Records triés, lus en mémoire ou fusionnés depuis les séries sur disque
+pub struct Sorted<T, F> {
+    memory: std::vec::IntoIter<T>,
+    runs: Vec<Run<T>>,
+    compare: F,
+}
+
+impl<T, F> Iterator for Sorted<T, F>
+where
+    T: Spill,
+    F: Fn(&T, &T) -> Ordering
+{
+    type Item = EtlResult<T>;
+
+    fn next(&mut self) -> Option<EtlResult<T>> {
+        if self.runs.is_empty() {
+            return self.memory.next().map(Ok);
+        }
+
+        // Peu de séries en pratique : un parcours linéaire suffit. À égalité,
+        // la série la plus ancienne passe d'abord, ce qui garde le tri stable.
+        let mut min: Option<usize> = None;
+        for (idx, run) in self.runs.iter().enumerate() {
+            if let Some(head) = &run.head {
+                let smaller = min.is_none_or(|m| {
+                    let current = self.runs[m].head.as_ref().unwrap();
+                    (self.compare)(head, current) == Ordering::Less
+                });
+                if smaller {
+                    min = Some(idx);
+                }
+            }
+        }
+
+        self.runs[min?].advance().transpose()
+    }
+}
+
+// Séries écrites par un tri, reportées dans les stats du pipeline
+pub(crate) fn record_spills<T, F>(stats: &mut PipelineStats, sorter: &ExternalSorter<T, F>)
+where
+    T: Spill + Send,
+    F: Fn(&T, &T) -> Ordering + Sync
+{
+    stats.spilled_runs += sorter.spilled_runs();
+    stats.spilled_bytes += sorter.spilled_bytes();
+}
+
+// Opérateurs à budget partagés par `Pipeline` et `StreamingPipeline` : les records
+// sont consommés un à un, et le résultat relu des séries au fil de l'itération.
+
+pub(crate) fn sort_items<T, F>(items: &mut dyn Iterator<Item = T>, budget: MemoryBudget, compare: F, stats: &mut PipelineStats)
+-> EtlResult<Sorted<T, F>>
+where
+    T: Spill + Send,
+    F: Fn(&T, &T) -> Ordering + Sync
+{
+    let mut sorter = ExternalSorter::new(budget, compare);
+    for item in items {
+        sorter.push(item)?;
+    }
+    record_spills(stats, &sorter);
+    sorter.finish()
+}
+
+/// Garde le premier de chaque groupe de records égaux selon `compare`, dans l'ordre d'origine.
+pub(crate) fn dedup_items<T, F>(items: &mut dyn Iterator<Item = T>, budget: MemoryBudget, compare: F, stats: &mut PipelineStats)
+-> EtlResult<impl Iterator<Item = EtlResult<T>> + use<T, F>>
+where
+    T: Spill + Send,
+    F: Fn(&T, &T) -> Ordering + Sync
+{
+    // Tri par (clé, position) : le premier de chaque groupe est l'original,
+    // puis retour à l'ordre d'origine par un second tri sur la position
+    let mut by_key = ExternalSorter::new(budget.clone(), |a: &Indexed<T>, b: &Indexed<T>| {
+        compare(&a.1, &b.1).then(a.0.cmp(&b.0))
+    });
+    for (idx, item) in items.enumerate() {
+        by_key.push(Indexed(idx, item))?;
+    }
+    record_spills(stats, &by_key);
+
+    let mut by_index = ExternalSorter::new(budget, |a: &Indexed<T>, b: &Indexed<T>| a.0.cmp(&b.0));
+    let mut first: Option<Indexed<T>> = None;
+    for item in by_key.finish()? {
+        let item = item?;
+        match &first {
+            Some(current) if compare(&current.1, &item.1) == Ordering::Equal => {},
+            _ => if let Some(current) = first.replace(item) {
+                by_index.push(current)?;
+            },
+        }
+    }
+    if let Some(current) = first {
+        by_index.push(current)?;
+    }
+    record_spills(stats, &by_index);
+
+    Ok(by_index.finish()?.map(|item| item.map(|Indexed(_, item)| item)))
+}
+
+/// Compte les records par clé ; les `(clé, compte)` sont rendus triés par clé.
+/// Les comptes partiels sont déversés triés quand la table dépasse le budget.
+pub(crate) fn count_items<T, K, F>(items: &mut dyn Iterator<Item = T>, budget: MemoryBudget, key_fn: F, stats: &mut PipelineStats)
+-> EtlResult<impl Iterator<Item = EtlResult<(K, usize)>> + use<T, K, F>>
+where
+    K: Ord + Hash + Spill + Send,
+    F: Fn(&T) -> K
+{
+    let mut sorter = ExternalSorter::new(budget.clone(), |a: &Counted<K>, b: &Counted<K>| a.0.cmp(&b.0));
+    let mut counts: HashMap<K, usize> = HashMap::new();
+    let mut counts_bytes = 0;
+
+    for item in items {
+        let key = key_fn(&item);
+        let size = key.estimated_size();
+        *counts.entry(key).or_insert_with(|| {
+            counts_bytes += size;
+            0
+        }) += 1;
+
+        if counts_bytes > budget.max_bytes {
+            for (key, count) in counts.drain() {
+                sorter.push(Counted(key, count))?;
+            }
+            counts_bytes = 0;
+        }
+    }
+    for (key, count) in counts {
+        sorter.push(Counted(key, count))?;
+    }
+    record_spills(stats, &sorter);
+
+    Ok(Summed { counts: sorter.finish()?.peekable() })
+}
+
+// Additionne les comptes consécutifs d'une même clé, rendus triés par clé
+struct Summed<I: Iterator> {
+    counts: Peekable<I>,
+}
+
+impl<K, I> Iterator for Summed<I>
+where
+    K: PartialEq,
+    I: Iterator<Item = EtlResult<Counted<K>>>
+{
+    type Item = EtlResult<(K, usize)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let Counted(key, mut count) = match self.counts.next()? {
+            Ok(counted) => counted,
+            Err(err) => return Some(Err(err)),
+        };
+        while let Some(Ok(next)) = self.counts.peek() {
+            if next.0 != key {
+                break;
+            }
+            count += next.1;
+            self.counts.next();
+        }
+        Some(Ok((key, count)))
+    }
+}
+
+// Répertoire propre à un test : des `cargo test` simultanés ne partagent pas leurs séries
+#[cfg(test)]
+pub(crate) fn test_spill_dir(name: &str) -> PathBuf {
+    static TEST_DIRS: AtomicUsize = AtomicUsize::new(0);
+    let id = TEST_DIRS.fetch_add(1, AtomicOrdering::Relaxed);
+    let dir = std::env::temp_dir().join(format!("{}-{}-{}", name, std::process::id(), id));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spilled_sort_is_stable() {
+        let dir = test_spill_dir("spill_sort");
+        let budget = MemoryBudget::new(200).with_dir(&dir);
+
+        let records: Vec<csv::StringRecord> = (0..100)
+            .map(|n| csv::StringRecord::from(vec![format!("{}", n % 7), format!("{}", n)]))
+            .collect();
+        let mut sorter = ExternalSorter::new(budget, |a: &csv::StringRecord, b: &csv::StringRecord| a[0].cmp(&b[0]));
+        for record in records.clone() {
+            sorter.push(record).unwrap();
+        }
+        assert!(sorter.spilled_runs() > 1);
+
+        let sorted: Vec<csv::StringRecord> = sorter.finish().unwrap().collect::<EtlResult<_>>().unwrap();
+        let mut expected = records;
+        expected.sort_by(|a, b| a[0].cmp(&b[0]));
+        assert_eq!(sorted, expected);
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+        std::fs::remove_dir(&dir).unwrap();
+    }
+}
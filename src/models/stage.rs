@@ -0,0 +1,196 @@
+use std::sync::mpsc::{sync_channel, Receiver, RecvError, SyncSender, TryRecvError, TrySendError};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use crate::models::pipeline::PipelineStats;
+
+// Mesures d'une file entre deux étapes ; `name` est l'étape qui la remplit.
+// Un producteur souvent bloqué (`send_stall`) signale un aval trop lent,
+// un consommateur qui attend (`recv_stall`) un amont trop lent.
+#[derive(Debug, Clone, Default)]
+pub struct StageStats {
+    pub name: String,
+    pub capacity: usize,
+    pub chunks: usize,
+    pub max_depth: usize,
+    // Somme des profondeurs vues à chaque réception, pour `mean_depth`
+    depth_total: usize,
+    pub send_stall: Duration,
+    pub recv_stall: Duration,
+}
+
+impl StageStats {
+    pub fn mean_depth(&self) -> f64 {
+        if self.chunks == 0 {
+            0.0
+        } else {
+            self.depth_total as f64 / self.chunks as f64
+        }
+    }
+}
+
+#[derive(Default)]
+struct Shared {
+    depth: AtomicUsize,
+    send_stall_ns: AtomicU64,
+}
+
+pub struct StageSender<T> {
+    sender: SyncSender<T>,
+    shared: Arc<Shared>,
+}
+
+pub struct StageReceiver<T> {
+    receiver: Receiver<T>,
+    shared: Arc<Shared>,
+    stats: StageStats,
+}
+
+pub fn channel<T>(name: &str, capacity: usize) -> (StageSender<T>, StageReceiver<T>) {
+    let capacity = capacity.max(1);
+    let (sender, receiver) = sync_channel(capacity);
+    let shared = Arc::new(Shared::default());
+    let stats = StageStats { name: name.to_string(), capacity, ..StageStats::default() };
+
+    (
+        StageSender { sender, shared: shared.clone() },
+        StageReceiver { receiver, shared, stats },
+    )
+}
+
+impl<T> StageSender<T> {
+    /// Bloque tant que la file est pleine ; échoue si le consommateur a été abandonné.
+    pub fn send(&self, value: T) -> Result<(), T> {
+        self.shared.depth.fetch_add(1, Ordering::Relaxed);
+
+        let result = match self.sender.try_send(value) {
+            Ok(()) => Ok(()),
+            Err(TrySendError::Full(value)) => {
+                let start = Instant::now();
+                let result = self.sender.send(value).map_err(|err| err.0);
+                self.shared.send_stall_ns.fetch_add(start.elapsed().as_nanos() as u64, Ordering::Relaxed);
+                result
+            },
+            Err(TrySendError::Disconnected(value)) => Err(value),
+        };
+
+        if result.is_err() {
+            self.shared.depth.fetch_sub(1, Ordering::Relaxed);
+        }
+        result
+    }
+}
+
+impl<T> StageReceiver<T> {
+    /// `None` quand le producteur a terminé.
+    pub fn recv(&mut self) -> Option<T> {
+        let value = match self.receiver.try_recv() {
+            Ok(value) => Ok(value),
+            Err(TryRecvError::Empty) => {
+                let start = Instant::now();
+                let value = self.receiver.recv();
+                self.stats.recv_stall += start.elapsed();
+                value
+            },
+            Err(TryRecvError::Disconnected) => Err(RecvError),
+        };
+
+        let value = value.ok()?;
+        // Un producteur bloqué compte déjà son chunk : on borne à la capacité
+        let depth = self.shared.depth.fetch_sub(1, Ordering::Relaxed).min(self.stats.capacity);
+        self.stats.chunks += 1;
+        self.stats.max_depth = self.stats.max_depth.max(depth);
+        self.stats.depth_total += depth;
+        Some(value)
+    }
+
+    pub fn stats(&self) -> StageStats {
+        StageStats {
+            send_stall: Duration::from_nanos(self.shared.send_stall_ns.load(Ordering::Relaxed)),
+            ..self.stats.clone()
+        }
+    }
+}
+
+// Exécute `chunks` sur son propre thread, démarré au premier `next`, et rend
+// ses chunks via une file bornée. Les mesures de la file sont reportées dans
+// le puits du pipeline quand la source est épuisée.
+pub struct StagedChunks<T> {
+    name: String,
+    capacity: usize,
+    pending: Option<Box<dyn Iterator<Item = Vec<T>> + Send>>,
+    receiver: Option<StageReceiver<Vec<T>>>,
+    sink: Arc<Mutex<PipelineStats>>,
+}
+
+impl<T: Send + 'static> StagedChunks<T> {
+    pub fn new<I>(name: &str, chunks: I, capacity: usize, sink: Arc<Mutex<PipelineStats>>) -> Self
+    where
+        I: Iterator<Item = Vec<T>> + Send + 'static
+    {
+        StagedChunks {
+            name: name.to_string(),
+            capacity,
+            pending: Some(Box::new(chunks)),
+            receiver: None,
+            sink,
+        }
+    }
+}
+
+impl<T: Send + 'static> Iterator for StagedChunks<T> {
+    type Item = Vec<T>;
+
+    fn next(&mut self) -> Option<Vec<T>> {
+        if let Some(chunks) = self.pending.take() {
+            let (sender, receiver) = channel(&self.name, self.capacity);
+            thread::spawn(move || {
+                for chunk in chunks {
+                    if sender.send(chunk).is_err() {
+                        break;
+                    }
+                }
+            });
+            self.receiver = Some(receiver);
+        }
+
+        let receiver = self.receiver.as_mut()?;
+        let chunk = receiver.recv();
+        if chunk.is_none() {
+            let stats = receiver.stats();
+            self.receiver = None;
+            self.sink.lock().unwrap().push_stage(stats);
+        }
+        chunk
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slow_consumer_stalls_producer() {
+        let (sender, mut receiver) = channel("extract", 2);
+
+        let producer = thread::spawn(move || {
+            for n in 0..6 {
+                sender.send(n).unwrap();
+            }
+        });
+
+        let mut values = Vec::new();
+        while let Some(value) = receiver.recv() {
+            thread::sleep(Duration::from_millis(5));
+            values.push(value);
+        }
+        producer.join().unwrap();
+
+        let stats = receiver.stats();
+        assert_eq!(values, (0..6).collect::<Vec<_>>());
+        assert_eq!(stats.chunks, 6);
+        assert_eq!(stats.max_depth, 2);
+        assert!(stats.send_stall > Duration::ZERO);
+    }
+}
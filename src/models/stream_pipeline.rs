@@ -1,8 +1,17 @@
-use std::sync::Arc;
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+use std::thread;
 use rayon::prelude::*;
+use rayon::iter::Either;
 
 use crate::models::csv_reader::CsvReader;
+use crate::models::error::{EtlError, EtlResult};
+use crate::models::input::{InputChunks, InputPort};
 use crate::models::pipeline::PipelineStats;
+use crate::models::spill::{count_items, dedup_items, sort_items, MemoryBudget, Spill};
+use crate::models::stage::{self, StagedChunks};
 
 pub struct StreamingPipeline<I, T>
 where
@@ -10,20 +19,171 @@ where
     T: Send + Sync
 {
     pub chunks: I,
-    pub stats: PipelineStats
+    pub stats: PipelineStats,
+    // Les chunks sont évalués paresseusement : les erreurs des étapes et les
+    // compteurs de la source sont collectés ici puis reportés dans les stats au `load`.
+    pub sink: Arc<Mutex<PipelineStats>>,
+    // Nombre maximal de chunks traités en même temps par une étape, et capacité
+    // des files entre extraction, transformations et `load`
+    pub in_flight: usize,
 }
 
+// Chunks d'un pipeline dont les étapes sont choisies à l'exécution (recettes)
+pub type BoxedChunks<T> = Box<dyn Iterator<Item = Vec<T>> + Send>;
 
-impl StreamingPipeline<CsvReader, csv::StringRecord> {
-    pub fn extract_streaming(path: &str, chunk_size: usize) -> Result<Self, Box<dyn std::error::Error>>
+fn default_in_flight() -> usize {
+    thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+// Traite jusqu'à `in_flight` chunks à la fois sur rayon et les rend dans l'ordre
+struct ParChunks<I: Iterator, U, F> {
+    chunks: I,
+    in_flight: usize,
+    ready: VecDeque<U>,
+    f: F,
+}
+
+impl<I: Iterator, U, F> ParChunks<I, U, F> {
+    fn new(chunks: I, in_flight: usize, f: F) -> Self {
+        ParChunks { chunks, in_flight: in_flight.max(1), ready: VecDeque::new(), f }
+    }
+}
+
+impl<I, U, F> Iterator for ParChunks<I, U, F>
+where
+    I: Iterator,
+    I::Item: Send,
+    U: Send,
+    F: Fn(I::Item) -> U + Send + Sync
+{
+    type Item = U;
+
+    fn next(&mut self) -> Option<U> {
+        if self.ready.is_empty() {
+            let window: Vec<I::Item> = self.chunks.by_ref().take(self.in_flight).collect();
+            self.ready = window.into_par_iter().map(&self.f).collect::<Vec<U>>().into();
+        }
+        self.ready.pop_front()
+    }
+}
+
+// Barrière des opérateurs à budget mémoire (tri, dédoublonnage, agrégation) :
+// le premier `next` passe toute la source à `drain`, qui déverse sur disque
+// au-delà du budget, puis le résultat fusionné est rendu par chunks de la
+// taille des chunks reçus. Une erreur d'écriture ou de relecture des séries
+// est bloquante : gardée dans le puits, elle arrête l'itération et fait échouer `load`.
+struct SpilledChunks<I, D, M> {
+    pending: Option<(I, D)>,
+    merged: Option<M>,
+    chunk_size: usize,
+    sink: Arc<Mutex<PipelineStats>>,
+}
+
+impl<I, D, M> SpilledChunks<I, D, M> {
+    fn new(chunks: I, drain: D, sink: Arc<Mutex<PipelineStats>>) -> Self {
+        SpilledChunks { pending: Some((chunks, drain)), merged: None, chunk_size: 1, sink }
+    }
+}
+
+impl<I, T, D, M, U> Iterator for SpilledChunks<I, D, M>
+where
+    I: Iterator<Item = Vec<T>>,
+    D: FnOnce(&mut dyn Iterator<Item = T>, &mut PipelineStats) -> EtlResult<M>,
+    M: Iterator<Item = EtlResult<U>>
+{
+    type Item = Vec<U>;
+
+    fn next(&mut self) -> Option<Vec<U>> {
+        if let Some((chunks, drain)) = self.pending.take() {
+            let mut chunk_size = 1;
+            let mut spills = PipelineStats::default();
+            let mut items = chunks.inspect(|chunk| chunk_size = chunk_size.max(chunk.len())).flatten();
+            let result = drain(&mut items, &mut spills);
+            drop(items);
+
+            self.chunk_size = chunk_size;
+            let mut sink = self.sink.lock().unwrap();
+            sink.merge(spills);
+            match result {
+                Ok(merged) => self.merged = Some(merged),
+                Err(err) => sink.set_fatal(err),
+            }
+        }
+
+        let merged = self.merged.as_mut()?;
+        let mut chunk = Vec::with_capacity(self.chunk_size);
+        for item in merged.by_ref().take(self.chunk_size) {
+            match item {
+                Ok(item) => chunk.push(item),
+                Err(err) => {
+                    self.sink.lock().unwrap().set_fatal(err);
+                    self.merged = None;
+                    break;
+                }
+            }
+        }
+
+        if chunk.is_empty() {
+            self.merged = None;
+            return None;
+        }
+        Some(chunk)
+    }
+}
+
+impl<I, T> StreamingPipeline<I, T>
+where
+    I: Iterator<Item = Vec<T>> + Send,
+    T: Send + Sync
+{
+    pub fn new(chunks: I) -> Self {
+        StreamingPipeline {
+            chunks,
+            stats: PipelineStats::default(),
+            sink: Arc::default(),
+            in_flight: default_in_flight(),
+        }
+    }
+
+    pub fn with_in_flight(mut self, in_flight: usize) -> Self {
+        self.in_flight = in_flight.max(1);
+        self
+    }
+
+    pub fn boxed(self) -> StreamingPipeline<BoxedChunks<T>, T>
+    where
+        I: 'static
+    {
+        StreamingPipeline {
+            chunks: Box::new(self.chunks),
+            stats: self.stats,
+            sink: self.sink,
+            in_flight: self.in_flight,
+        }
+    }
+}
+
+impl StreamingPipeline<StagedChunks<csv::StringRecord>, csv::StringRecord> {
+    pub fn extract_streaming(path: &str, chunk_size: usize) -> EtlResult<Self>
     {
         let reader = CsvReader::new(path, chunk_size)?;
-        Ok(
-            StreamingPipeline {
-                chunks: reader,
-                stats: PipelineStats::default(),
-            }
-        )
+        let mut pipeline = StreamingPipeline::from_input(reader);
+        pipeline.stats.set_sources(vec![path.to_string()]);
+        Ok(pipeline)
+    }
+
+    // La source est lue sur son propre thread, en avance sur les transformations
+    pub fn from_input<P: InputPort + 'static>(port: P) -> Self {
+        let sink: Arc<Mutex<PipelineStats>> = Arc::default();
+        let in_flight = default_in_flight();
+        let chunks = InputChunks::new(port, sink.clone());
+
+        StreamingPipeline {
+            chunks: StagedChunks::new("extract", chunks, in_flight, sink.clone()),
+            stats: PipelineStats::default(),
+            sink,
+            in_flight,
+        }
     }
 }
 
@@ -37,16 +197,81 @@ where
         F: Fn(T) -> U + Send + Sync,
         U: Send + Sync
     {
-        let transformed_chunks = self.chunks
-            .map(move |chunk| {
-                chunk.into_iter()
-                    .map(|item| f(item))
-                    .collect::<Vec<U>>()
+        let transformed_chunks = ParChunks::new(self.chunks, self.in_flight, move |chunk: Vec<T>| {
+            chunk.into_par_iter()
+                .map(&f)
+                .collect::<Vec<U>>()
+        });
+
+        StreamingPipeline {
+            chunks: transformed_chunks,
+            stats: self.stats,
+            sink: self.sink,
+            in_flight: self.in_flight,
+        }
+    }
+
+    pub fn try_transform<F, U>(self, f: F) -> StreamingPipeline<impl Iterator<Item = Vec<U>>, U>
+    where
+        F: Fn(T) -> Result<U, String> + Send + Sync,
+        U: Send + Sync
+    {
+        let sink = self.sink.clone();
+        let mut offset = 0;
+
+        let numbered = self.chunks.map(move |chunk| {
+            let start = offset;
+            offset += chunk.len();
+            (start, chunk)
+        });
+
+        let transformed_chunks = ParChunks::new(numbered, self.in_flight, move |(start, chunk): (usize, Vec<T>)| {
+            chunk.into_par_iter()
+                .enumerate()
+                .partition_map::<Vec<U>, Vec<String>, _, _, _>(|(idx, item)| match f(item) {
+                    Ok(item) => Either::Left(item),
+                    Err(err) => Either::Right(format!("{} record transform error: {}", start + idx, err)),
+                })
+        })
+            // Erreurs reportées chunk par chunk, dans l'ordre des records
+            .map(move |(transformed, failed)| {
+                if !failed.is_empty() {
+                    sink.lock().unwrap().push_errors(failed);
+                }
+                transformed
+            });
+
+        StreamingPipeline {
+            chunks: transformed_chunks,
+            stats: self.stats,
+            sink: self.sink,
+            in_flight: self.in_flight,
+        }
+    }
+
+    /// Un chunk en erreur est retiré en entier et l'erreur est reportée dans les stats.
+    pub fn try_transform_chunks<F, U>(self, f: F) -> StreamingPipeline<impl Iterator<Item = Vec<U>>, U>
+    where
+        F: Fn(Vec<T>) -> Result<Vec<U>, String> + Send + Sync,
+        U: Send + Sync
+    {
+        let sink = self.sink.clone();
+
+        let transformed_chunks = ParChunks::new(self.chunks.enumerate(), self.in_flight, move |(idx, chunk): (usize, Vec<T>)| {
+            f(chunk).map_err(|err| format!("{} chunk transform error: {}", idx, err))
+        })
+            .map(move |result| {
+                result.unwrap_or_else(|err| {
+                    sink.lock().unwrap().push_errors([err]);
+                    Vec::new()
+                })
             });
 
         StreamingPipeline {
             chunks: transformed_chunks,
-            stats: self.stats
+            stats: self.stats,
+            sink: self.sink,
+            in_flight: self.in_flight,
         }
     }
 
@@ -54,33 +279,272 @@ where
     where
         P: Fn(&T) -> bool + Send + Sync + 'static
     {
-        let pred = Arc::new(predicate);
-
-        let filtered_chunk = self.chunks.map(move |chunk| {
-            let pred = pred.clone(); // Clone l'Arc (pas la closure)
-            chunk.into_iter().filter(move |item| pred(item)).collect()
+        let filtered_chunk = ParChunks::new(self.chunks, self.in_flight, move |chunk: Vec<T>| {
+            chunk.into_par_iter()
+                .filter(|item| predicate(item))
+                .collect::<Vec<T>>()
         });
 
         StreamingPipeline {
             chunks: filtered_chunk,
-            stats: self.stats
+            stats: self.stats,
+            sink: self.sink,
+            in_flight: self.in_flight,
+        }
+    }
+
+    /// Tri externe stable : jusqu'à `budget` en mémoire, puis fusion de séries
+    /// triées sur disque. Rien n'est rendu avant que toute la source soit lue.
+    pub fn sort_by<F>(self, budget: MemoryBudget, compare: F) -> StreamingPipeline<impl Iterator<Item = Vec<T>>, T>
+    where
+        T: Spill,
+        F: Fn(&T, &T) -> Ordering + Send + Sync
+    {
+        let sorted_chunks = SpilledChunks::new(self.chunks, move |items: &mut dyn Iterator<Item = T>, stats: &mut PipelineStats| {
+            sort_items(items, budget, compare, stats)
+        }, self.sink.clone());
+
+        StreamingPipeline {
+            chunks: sorted_chunks,
+            stats: self.stats,
+            sink: self.sink,
+            in_flight: self.in_flight,
+        }
+    }
+
+    /// Garde le premier de chaque groupe de records égaux selon `compare`,
+    /// dans l'ordre d'origine, avec au plus `budget` en mémoire par tri.
+    pub fn dedup_by<F>(self, budget: MemoryBudget, compare: F) -> StreamingPipeline<impl Iterator<Item = Vec<T>>, T>
+    where
+        T: Spill,
+        F: Fn(&T, &T) -> Ordering + Send + Sync
+    {
+        let deduped_chunks = SpilledChunks::new(self.chunks, move |items: &mut dyn Iterator<Item = T>, stats: &mut PipelineStats| {
+            dedup_items(items, budget, compare, stats)
+        }, self.sink.clone());
+
+        StreamingPipeline {
+            chunks: deduped_chunks,
+            stats: self.stats,
+            sink: self.sink,
+            in_flight: self.in_flight,
         }
     }
 
-    pub fn load<F>(mut self, mut loader: F) -> Result<PipelineStats, Box<dyn std::error::Error>>
+    /// Compte les records par clé et rend les `(clé, compte)` triés par clé.
+    /// Les comptes partiels sont déversés triés quand la table dépasse `budget`.
+    pub fn aggregate<K, F>(self, budget: MemoryBudget, key_fn: F) -> StreamingPipeline<impl Iterator<Item = Vec<(K, usize)>>, (K, usize)>
     where
-        F: FnMut(&[T]) -> Result<(), Box<dyn std::error::Error>>
+        K: Ord + Hash + Spill + Send + Sync,
+        F: Fn(&T) -> K + Send + Sync
     {
-        for chunk in self.chunks {
-            loader(&chunk)?;
-            self.stats.total_filtered += chunk.len();
+        let counted_chunks = SpilledChunks::new(self.chunks, move |items: &mut dyn Iterator<Item = T>, stats: &mut PipelineStats| {
+            count_items(items, budget, key_fn, stats)
+        }, self.sink.clone());
+
+        StreamingPipeline {
+            chunks: counted_chunks,
+            stats: self.stats,
+            sink: self.sink,
+            in_flight: self.in_flight,
         }
+    }
+
+    // Les transformations tournent sur un thread dédié pendant que `loader` écrit
+    // les chunks déjà prêts ; au plus `in_flight` chunks attendent d'être chargés.
+    // Un `loader` lent bloque les étapes amont au lieu d'accumuler les chunks.
+    // Une erreur bloquante de la source est renvoyée après les chunks déjà chargés.
+    pub fn load<F>(mut self, mut loader: F) -> EtlResult<PipelineStats>
+    where
+        F: FnMut(&[T]) -> EtlResult<()>
+    {
+        let (sender, receiver) = stage::channel("transform", self.in_flight);
+        let chunks = self.chunks;
+
+        let (loaded, queue) = thread::scope(|scope| {
+            scope.spawn(move || {
+                for chunk in chunks {
+                    // Le consommateur s'est arrêté sur une erreur
+                    if sender.send(chunk).is_err() {
+                        break;
+                    }
+                }
+            });
+
+            // Déplacé ici pour être libéré avant la fin du scope en cas d'erreur
+            let mut receiver = receiver;
+            let mut loaded = 0;
+            while let Some(chunk) = receiver.recv() {
+                loader(&chunk)?;
+                loaded += chunk.len();
+            }
+            Ok::<_, EtlError>((loaded, receiver.stats()))
+        })?;
+        self.stats.total_filtered += loaded;
+        self.sink.lock().unwrap().push_stage(queue);
 
-        Ok(self.stats)
+        let sink = std::mem::take(&mut *self.sink.lock().unwrap());
+        self.stats.merge(sink);
+
+        // Source ou étape arrêtée en cours de route : le chargement est partiel
+        match self.stats.take_fatal() {
+            Some(err) => Err(err),
+            None => Ok(self.stats),
+        }
     }
 }
 
 #[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::pipeline::Pipeline;
+    use crate::models::sort::{SortKey, SortOrder};
+    use crate::models::spill::test_spill_dir;
+
+    #[test]
+    fn test_parallel_chunks_keep_order() -> EtlResult<()> {
+        let chunks = (0..20).map(|n| (n * 100..(n + 1) * 100).collect::<Vec<usize>>());
+
+        let mut loaded = Vec::new();
+        let stats = StreamingPipeline::new(chunks)
+            .with_in_flight(3)
+            .transform(|n| n * 2)
+            .filter(|n| n % 3 == 0)
+            .try_transform(|n| if n % 7 == 0 { Err("multiple de 7".to_string()) } else { Ok(n) })
+            .load(|chunk| {
+                loaded.extend_from_slice(chunk);
+                Ok(())
+            })?;
+
+        let expected: Vec<usize> = (0..2000).map(|n| n * 2).filter(|n| n % 3 == 0 && n % 7 != 0).collect();
+        assert_eq!(loaded, expected);
+        assert_eq!(stats.total_filtered, expected.len());
+        assert!(stats.errors()[0].starts_with("0 record transform error"));
+        assert!(stats.errors()[1].starts_with("7 record transform error"));
+        assert_eq!(stats.stages().len(), 1);
+        assert_eq!(stats.stages()[0].chunks, 20);
+        assert!(stats.stages()[0].max_depth <= 3);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_streaming_sort_spills_and_keeps_chunk_size() -> EtlResult<()> {
+        let order = SortOrder::new(&[SortKey::desc("ville"), SortKey::asc("login")], &["login", "ville"])?;
+        let records: Vec<csv::StringRecord> = (0..300)
+            .map(|n| csv::StringRecord::from(vec![format!("user{:03}", n), format!("ville{}", n % 7)]))
+            .collect();
+        let chunks: Vec<Vec<csv::StringRecord>> = records.chunks(40).map(|chunk| chunk.to_vec()).collect();
+
+        let dir = test_spill_dir("streaming_sort");
+        let mut loaded = Vec::new();
+        let mut sizes = Vec::new();
+        let stats = StreamingPipeline::new(chunks.into_iter())
+            .sort_by(MemoryBudget::new(2000).with_dir(&dir), move |a, b| order.compare(a, b))
+            .load(|chunk| {
+                sizes.push(chunk.len());
+                loaded.extend_from_slice(chunk);
+                Ok(())
+            })?;
+
+        let mut expected = records;
+        expected.sort_by(|a, b| b[1].cmp(&a[1]).then(a[0].cmp(&b[0])));
+        assert_eq!(loaded, expected);
+        assert!(stats.spilled_runs > 1);
+        assert_eq!(sizes[0], 40);
+        std::fs::remove_dir(&dir).unwrap();
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_spilled_dedup_and_aggregate_match_in_memory() -> EtlResult<()> {
+        let dir = test_spill_dir("streaming_spill");
+        let records: Vec<csv::StringRecord> = (0..500)
+            .map(|n| csv::StringRecord::from(vec![format!("ville{}", n % 13), format!("user{}", n)]))
+            .collect();
+        let chunks = || records.chunks(40).map(|chunk| chunk.to_vec()).collect::<Vec<_>>().into_iter();
+        // ~50 clés : la table des comptes dépasse le petit budget
+        let prefix = |r: &csv::StringRecord| r[1][..r[1].len() - 1].to_string();
+
+        for max_bytes in [usize::MAX, 1000] {
+            let budget = MemoryBudget::new(max_bytes).with_dir(&dir);
+
+            let mut deduped = Vec::new();
+            let stats = StreamingPipeline::new(chunks())
+                .dedup_by(budget.clone(), |a, b| a[0].cmp(&b[0]))
+                .load(|chunk| {
+                    deduped.extend_from_slice(chunk);
+                    Ok(())
+                })?;
+            assert_eq!(deduped, records[..13]);
+            assert_eq!(stats.spilled_runs > 0, max_bytes == 1000);
+
+            let mut counts = Vec::new();
+            StreamingPipeline::new(chunks())
+                .aggregate(budget, prefix)
+                .load(|chunk| {
+                    counts.extend_from_slice(chunk);
+                    Ok(())
+                })?;
+            let mut expected: Vec<(String, usize)> = Pipeline { data: records.clone(), ..Default::default() }
+                .aggregate(prefix)?
+                .into_iter()
+                .collect();
+            expected.sort();
+            assert_eq!(counts, expected);
+        }
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+        std::fs::remove_dir(&dir).unwrap();
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_fatal_source_error_fails_load() {
+        // Un chunk, puis une erreur de lecture
+        struct Truncated(usize);
+        impl InputPort for Truncated {
+            fn read_chunk(&mut self) -> EtlResult<Option<Vec<csv::StringRecord>>> {
+                self.0 += 1;
+                match self.0 {
+                    1 => Ok(Some(vec![csv::StringRecord::from(vec!["jdupont"])])),
+                    _ => Err(EtlError::io("users.csv.gz", std::io::Error::other("corrupt deflate stream"))),
+                }
+            }
+        }
+
+        let mut loaded = 0;
+        let result = StreamingPipeline::from_input(Truncated(0)).load(|chunk| {
+            loaded += chunk.len();
+            Ok(())
+        });
+        assert!(matches!(result, Err(EtlError::Io { .. })));
+        assert_eq!(loaded, 1);
+    }
+
+    #[test]
+    fn test_slow_loader_applies_backpressure() -> EtlResult<()> {
+        let stats = StreamingPipeline::extract_streaming("./src/data/data_4.csv", 50)?
+            .with_in_flight(2)
+            .filter(|record| !record.is_empty())
+            .load(|_| {
+                thread::sleep(std::time::Duration::from_millis(5));
+                Ok(())
+            })?;
+
+        let names: Vec<&str> = stats.stages().iter().map(|stage| stage.name.as_str()).collect();
+        assert_eq!(names, ["extract", "transform"]);
+        let transform = &stats.stages()[1];
+        assert_eq!(transform.chunks, stats.stages()[0].chunks);
+        assert!(transform.max_depth <= 2);
+        assert!(transform.send_stall > std::time::Duration::ZERO);
+
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "sqlite"))]
 mod test {
     use std::time::Instant;
     use crate::adapter::storage_output::sqlite::SqliteAdapter;
@@ -1,4 +1,5 @@
 use crate::models::error::{ValidationError, ValidationResult};
+use crate::models::record::Record;
 
 #[derive(Debug)]
 pub struct User {
@@ -26,4 +27,14 @@ impl User {
             Err(errors)
         }
     }
-}
\ No newline at end of file
+}
+
+impl Record for User {
+    fn field_names() -> Vec<&'static str> {
+        vec!["username", "identifier", "first_name", "last_name"]
+    }
+
+    fn field_values(&self) -> Vec<&str> {
+        vec![&self.username, &self.identifier, &self.first_name, &self.last_name]
+    }
+}
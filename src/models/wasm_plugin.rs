@@ -0,0 +1,271 @@
+use std::fs;
+use std::sync::Mutex;
+use wasmi::{Config, Engine, Instance, Linker, Memory, Module, Store, StoreLimits, StoreLimitsBuilder, TypedFunc};
+use crate::models::error::{EtlError, EtlResult};
+use crate::models::user::User;
+
+// ABI attendu d'un plugin :
+//   - export `memory`
+//   - export `alloc(len: i32) -> i32` : réserve `len` octets et renvoie leur adresse
+//   - export `reset()` : libère tout ce qui a été alloué, appelé après chaque record
+//   - export `transform(ptr: i32, len: i32) -> i64` : lit un record et renvoie
+//     `(out_ptr << 32) | out_len`, ou une valeur négative en cas d'erreur
+// Un record est encodé en UTF-8, champs séparés par FIELD_SEPARATOR, dans l'ordre
+// username, identifier, first_name, last_name.
+pub const FIELD_SEPARATOR: char = '\u{1f}';
+
+pub const DEFAULT_FUEL: u64 = 1_000_000;
+pub const DEFAULT_MEMORY_LIMIT: usize = 16 * 1024 * 1024;
+
+pub struct WasmPlugin {
+    path: String,
+    engine: Engine,
+    module: Module,
+    fuel: u64,
+    memory_limit: usize,
+    // Instances réutilisables, une instance n'étant utilisée que par un thread à la fois
+    pool: Mutex<Vec<PluginInstance>>,
+}
+
+struct PluginInstance {
+    store: Store<StoreLimits>,
+    memory: Memory,
+    alloc: TypedFunc<i32, i32>,
+    reset: TypedFunc<(), ()>,
+    transform: TypedFunc<(i32, i32), i64>,
+}
+
+impl std::fmt::Debug for WasmPlugin {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "WasmPlugin({})", self.path)
+    }
+}
+
+impl WasmPlugin {
+    pub fn load(path: &str) -> EtlResult<Self> {
+        let bytes = fs::read(path).map_err(|err| EtlError::io(path, err))?;
+        WasmPlugin::from_bytes(path, &bytes)
+    }
+
+    pub fn from_bytes(name: &str, bytes: &[u8]) -> EtlResult<Self> {
+        let mut config = Config::default();
+        config.consume_fuel(true);
+
+        let engine = Engine::new(&config);
+        let module = Module::new(&engine, bytes)
+            .map_err(|err| plugin_error(name, err))?;
+
+        let plugin = WasmPlugin {
+            path: name.to_string(),
+            engine,
+            module,
+            fuel: DEFAULT_FUEL,
+            memory_limit: DEFAULT_MEMORY_LIMIT,
+            pool: Mutex::new(Vec::new()),
+        };
+
+        // Vérifie l'ABI dès le chargement plutôt qu'au premier record
+        let instance = plugin.instantiate()?;
+        plugin.pool.lock().unwrap().push(instance);
+
+        Ok(plugin)
+    }
+
+    pub fn with_limits(mut self, fuel: u64, memory_limit: usize) -> Self {
+        self.fuel = fuel;
+        self.memory_limit = memory_limit;
+        self.pool.get_mut().unwrap().clear();
+        self
+    }
+
+    pub fn run_on_user(&self, user: User) -> Result<User, String> {
+        let mut instance = match self.pool.lock().unwrap().pop() {
+            Some(instance) => instance,
+            None => self.instantiate().map_err(|err| err.to_string())?,
+        };
+
+        let result = instance.call(&encode_user(&user), self.fuel);
+
+        // Après un trap l'état de l'instance n'est plus fiable : on la jette
+        if result.is_ok() {
+            self.pool.lock().unwrap().push(instance);
+        }
+
+        result.and_then(|bytes| decode_user(&bytes))
+            .map_err(|err| format!("plugin {}: {}", self.path, err))
+    }
+
+    fn instantiate(&self) -> EtlResult<PluginInstance> {
+        let limits = StoreLimitsBuilder::new()
+            .memory_size(self.memory_limit)
+            .instances(1)
+            .build();
+
+        let mut store = Store::new(&self.engine, limits);
+        store.limiter(|limits| limits);
+        store.set_fuel(self.fuel).map_err(|err| plugin_error(&self.path, err))?;
+
+        // Aucun import n'est fourni : le plugin n'a accès à rien hors de sa mémoire
+        let linker = Linker::<StoreLimits>::new(&self.engine);
+        let instance: Instance = linker
+            .instantiate(&mut store, &self.module)
+            .and_then(|pre| pre.start(&mut store))
+            .map_err(|err| plugin_error(&self.path, err))?;
+
+        let memory = instance.get_memory(&store, "memory")
+            .ok_or_else(|| plugin_error(&self.path, "le plugin doit exporter `memory`"))?;
+        let alloc = instance.get_typed_func::<i32, i32>(&store, "alloc")
+            .map_err(|err| plugin_error(&self.path, err))?;
+        let reset = instance.get_typed_func::<(), ()>(&store, "reset")
+            .map_err(|err| plugin_error(&self.path, err))?;
+        let transform = instance.get_typed_func::<(i32, i32), i64>(&store, "transform")
+            .map_err(|err| plugin_error(&self.path, err))?;
+
+        Ok(PluginInstance { store, memory, alloc, reset, transform })
+    }
+}
+
+impl PluginInstance {
+    fn call(&mut self, input: &[u8], fuel: u64) -> Result<Vec<u8>, String> {
+        self.store.set_fuel(fuel).map_err(|err| err.to_string())?;
+
+        let output = self.transform_record(input)?;
+
+        // Sans reset, les allocations s'accumulent d'un record à l'autre jusqu'à saturer la mémoire
+        self.reset.call(&mut self.store, ()).map_err(|err| err.to_string())?;
+
+        Ok(output)
+    }
+
+    fn transform_record(&mut self, input: &[u8]) -> Result<Vec<u8>, String> {
+        let len = i32::try_from(input.len()).map_err(|_| "record trop grand".to_string())?;
+        let ptr = self.alloc.call(&mut self.store, len).map_err(|err| err.to_string())?;
+
+        self.memory.write(&mut self.store, ptr as u32 as usize, input)
+            .map_err(|err| err.to_string())?;
+
+        let packed = self.transform.call(&mut self.store, (ptr, len))
+            .map_err(|err| err.to_string())?;
+
+        if packed < 0 {
+            return Err(format!("le plugin a renvoyé le code d'erreur {}", packed));
+        }
+
+        let out_ptr = (packed >> 32) as usize;
+        let out_len = (packed & 0xffff_ffff) as usize;
+
+        let mut output = vec![0; out_len];
+        self.memory.read(&self.store, out_ptr, &mut output)
+            .map_err(|err| err.to_string())?;
+
+        Ok(output)
+    }
+}
+
+fn plugin_error(path: &str, reason: impl std::fmt::Display) -> EtlError {
+    EtlError::Plugin { path: path.to_string(), reason: reason.to_string() }
+}
+
+fn encode_user(user: &User) -> Vec<u8> {
+    [
+        user.username.as_str(),
+        user.identifier.as_str(),
+        user.first_name.as_str(),
+        user.last_name.as_str(),
+    ]
+        .join(&FIELD_SEPARATOR.to_string())
+        .into_bytes()
+}
+
+fn decode_user(bytes: &[u8]) -> Result<User, String> {
+    let text = std::str::from_utf8(bytes).map_err(|err| format!("sortie non UTF-8: {}", err))?;
+    let fields: Vec<&str> = text.split(FIELD_SEPARATOR).collect();
+
+    match fields.as_slice() {
+        [username, identifier, first_name, last_name] => Ok(User {
+            username: username.to_string(),
+            identifier: identifier.to_string(),
+            first_name: first_name.to_string(),
+            last_name: last_name.to_string(),
+        }),
+        _ => Err(format!("4 champs attendus, {} reçus", fields.len())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Met le record en majuscules (ASCII) sur place ; renvoie -1 si le record est vide
+    const UPPERCASE_WAT: &str = r#"
+        (module
+            (memory (export "memory") 1)
+            (global $next (mut i32) (i32.const 1024))
+            (func (export "alloc") (param $len i32) (result i32)
+                (local $ptr i32)
+                (local.set $ptr (global.get $next))
+                (global.set $next (i32.add (global.get $next) (local.get $len)))
+                (local.get $ptr))
+            (func (export "reset")
+                (global.set $next (i32.const 1024)))
+            (func (export "transform") (param $ptr i32) (param $len i32) (result i64)
+                (local $i i32)
+                (local $c i32)
+                (if (i32.eqz (local.get $len)) (then (return (i64.const -1))))
+                (block $done
+                    (loop $next_byte
+                        (br_if $done (i32.ge_u (local.get $i) (local.get $len)))
+                        (local.set $c (i32.load8_u (i32.add (local.get $ptr) (local.get $i))))
+                        (if (i32.and
+                                (i32.ge_u (local.get $c) (i32.const 97))
+                                (i32.le_u (local.get $c) (i32.const 122)))
+                            (then (i32.store8
+                                (i32.add (local.get $ptr) (local.get $i))
+                                (i32.sub (local.get $c) (i32.const 32)))))
+                        (local.set $i (i32.add (local.get $i) (i32.const 1)))
+                        (br $next_byte)))
+                (i64.or
+                    (i64.shl (i64.extend_i32_u (local.get $ptr)) (i64.const 32))
+                    (i64.extend_i32_u (local.get $len)))))
+    "#;
+
+    fn user() -> User {
+        User {
+            username: "jdupont".to_string(),
+            identifier: "id-1".to_string(),
+            first_name: "jean".to_string(),
+            last_name: "dupont".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_wasm_plugin_transform() {
+        let bytes = wat::parse_str(UPPERCASE_WAT).unwrap();
+        let plugin = WasmPlugin::from_bytes("uppercase", &bytes).unwrap();
+
+        let user = plugin.run_on_user(user()).unwrap();
+
+        assert_eq!(user.username, "JDUPONT");
+        assert_eq!(user.last_name, "DUPONT");
+    }
+
+    #[test]
+    fn test_wasm_plugin_reuses_memory() {
+        let bytes = wat::parse_str(UPPERCASE_WAT).unwrap();
+        let plugin = WasmPlugin::from_bytes("uppercase", &bytes).unwrap();
+
+        // ~25 octets par record : bien plus que la page de 64 Kio du plugin sans reset
+        for _ in 0..10_000 {
+            assert_eq!(plugin.run_on_user(user()).unwrap().first_name, "JEAN");
+        }
+    }
+
+    #[test]
+    fn test_wasm_plugin_fuel_limit() {
+        let bytes = wat::parse_str(UPPERCASE_WAT).unwrap();
+        let plugin = WasmPlugin::from_bytes("uppercase", &bytes).unwrap()
+            .with_limits(10, DEFAULT_MEMORY_LIMIT);
+
+        assert!(plugin.run_on_user(user()).is_err());
+    }
+}
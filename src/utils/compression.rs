@@ -0,0 +1,203 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use crate::models::error::{EtlError, EtlResult};
+
+// Chemin désignant l'entrée standard (source) ou la sortie standard (sortie)
+pub const STDIO_PATH: &str = "-";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    Gzip,
+    Zstd,
+    Bzip2,
+}
+
+impl Compression {
+    pub fn from_name(name: &str) -> Option<Compression> {
+        match name {
+            "none" => Some(Compression::None),
+            "gzip" | "gz" => Some(Compression::Gzip),
+            "zstd" | "zst" => Some(Compression::Zstd),
+            "bzip2" | "bz2" => Some(Compression::Bzip2),
+            _ => None
+        }
+    }
+
+    pub fn from_extension(path: &str) -> Compression {
+        path.rsplit_once('.')
+            .and_then(|(_, ext)| match ext {
+                "gz" | "zst" | "bz2" => Compression::from_name(ext),
+                _ => None
+            })
+            .unwrap_or(Compression::None)
+    }
+
+    pub fn from_magic_bytes(bytes: &[u8]) -> Option<Compression> {
+        if bytes.starts_with(&[0x1f, 0x8b]) {
+            Some(Compression::Gzip)
+        } else if bytes.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            Some(Compression::Zstd)
+        } else if bytes.starts_with(b"BZh") && matches!(bytes.get(3), Some(b'1'..=b'9')) {
+            Some(Compression::Bzip2)
+        } else {
+            None
+        }
+    }
+}
+
+// Ouvre un fichier (ou stdin pour `-`) en décompressant si besoin : les magic
+// bytes priment sur l'extension, un `.csv.gz` mal nommé est donc quand même lu.
+pub fn open_reader(path: &str) -> EtlResult<Box<dyn Read + Send>> {
+    let input: Box<dyn Read + Send> = if path == STDIO_PATH {
+        Box::new(io::stdin())
+    } else {
+        Box::new(File::open(path).map_err(|err| EtlError::io(path, err))?)
+    };
+    let mut reader = BufReader::new(input);
+
+    let head = reader.fill_buf().map_err(|err| EtlError::io(path, err))?;
+    let compression = Compression::from_magic_bytes(head)
+        .unwrap_or_else(|| Compression::from_extension(path));
+
+    decoder(path, reader, compression)
+}
+
+#[cfg(feature = "compression")]
+fn decoder(path: &str, reader: BufReader<Box<dyn Read + Send>>, compression: Compression) -> EtlResult<Box<dyn Read + Send>> {
+    Ok(match compression {
+        Compression::None => Box::new(reader),
+        Compression::Gzip => Box::new(flate2::bufread::MultiGzDecoder::new(reader)),
+        Compression::Zstd => Box::new(zstd::Decoder::with_buffer(reader).map_err(|err| EtlError::io(path, err))?),
+        Compression::Bzip2 => Box::new(bzip2::bufread::MultiBzDecoder::new(reader)),
+    })
+}
+
+#[cfg(not(feature = "compression"))]
+fn decoder(path: &str, reader: BufReader<Box<dyn Read + Send>>, compression: Compression) -> EtlResult<Box<dyn Read + Send>> {
+    match compression {
+        Compression::None => Ok(Box::new(reader)),
+        other => Err(EtlError::InvalidSource {
+            path: path.to_string(),
+            reason: format!("fichier compressé ({:?}) : nécessite la feature `compression`", other),
+        }),
+    }
+}
+
+type Sink = BufWriter<Box<dyn Write + Send>>;
+
+// Destination d'un adapter de sortie texte (fichier, ou stdout pour `-`).
+// `finish` doit être appelé pour écrire la fin du flux compressé et remonter
+// les erreurs d'écriture.
+pub enum OutputWriter {
+    Plain(Sink),
+    #[cfg(feature = "compression")]
+    Gzip(flate2::write::GzEncoder<Sink>),
+    #[cfg(feature = "compression")]
+    Zstd(zstd::Encoder<'static, Sink>),
+    #[cfg(feature = "compression")]
+    Bzip2(bzip2::write::BzEncoder<Sink>),
+}
+
+impl OutputWriter {
+    // `level` : 0-9 pour gzip/bzip2, 1-22 pour zstd ; niveau par défaut du codec sinon
+    #[cfg_attr(not(feature = "compression"), allow(unused_variables))]
+    pub fn create(path: &str, compression: Compression, level: Option<i32>) -> EtlResult<OutputWriter> {
+        let output: Box<dyn Write + Send> = if path == STDIO_PATH {
+            Box::new(io::stdout())
+        } else {
+            Box::new(File::create(path).map_err(|err| EtlError::io(path, err))?)
+        };
+        let file = BufWriter::new(output);
+
+        match compression {
+            Compression::None => Ok(OutputWriter::Plain(file)),
+            #[cfg(feature = "compression")]
+            Compression::Gzip => {
+                let level = level.map_or(flate2::Compression::default(), |l| flate2::Compression::new(l.clamp(0, 9) as u32));
+                Ok(OutputWriter::Gzip(flate2::write::GzEncoder::new(file, level)))
+            },
+            #[cfg(feature = "compression")]
+            Compression::Zstd => {
+                let encoder = zstd::Encoder::new(file, level.unwrap_or(zstd::DEFAULT_COMPRESSION_LEVEL))
+                    .map_err(|err| EtlError::io(path, err))?;
+                Ok(OutputWriter::Zstd(encoder))
+            },
+            #[cfg(feature = "compression")]
+            Compression::Bzip2 => {
+                let level = level.map_or(bzip2::Compression::default(), |l| bzip2::Compression::new(l.clamp(1, 9) as u32));
+                Ok(OutputWriter::Bzip2(bzip2::write::BzEncoder::new(file, level)))
+            },
+            #[cfg(not(feature = "compression"))]
+            other => Err(EtlError::InvalidRecipe(
+                format!("compression {:?} : nécessite la feature `compression`", other)
+            )),
+        }
+    }
+
+    pub fn finish(self) -> io::Result<()> {
+        match self {
+            OutputWriter::Plain(mut file) => file.flush(),
+            #[cfg(feature = "compression")]
+            OutputWriter::Gzip(encoder) => encoder.finish()?.flush(),
+            #[cfg(feature = "compression")]
+            OutputWriter::Zstd(encoder) => encoder.finish()?.flush(),
+            #[cfg(feature = "compression")]
+            OutputWriter::Bzip2(encoder) => encoder.finish()?.flush(),
+        }
+    }
+}
+
+impl Write for OutputWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            OutputWriter::Plain(w) => w.write(buf),
+            #[cfg(feature = "compression")]
+            OutputWriter::Gzip(w) => w.write(buf),
+            #[cfg(feature = "compression")]
+            OutputWriter::Zstd(w) => w.write(buf),
+            #[cfg(feature = "compression")]
+            OutputWriter::Bzip2(w) => w.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            OutputWriter::Plain(w) => w.flush(),
+            #[cfg(feature = "compression")]
+            OutputWriter::Gzip(w) => w.flush(),
+            #[cfg(feature = "compression")]
+            OutputWriter::Zstd(w) => w.flush(),
+            #[cfg(feature = "compression")]
+            OutputWriter::Bzip2(w) => w.flush(),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "compression"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compressed_round_trip() {
+        for (ext, compression) in [("gz", Compression::Gzip), ("zst", Compression::Zstd), ("bz2", Compression::Bzip2)] {
+            let path = std::env::temp_dir().join(format!("compression_round_trip.csv.{}", ext));
+            let path = path.to_str().unwrap();
+
+            let mut writer = OutputWriter::create(path, Compression::from_extension(path), Some(3)).unwrap();
+            writer.write_all(b"a,b\n1,2\n").unwrap();
+            writer.finish().unwrap();
+
+            let mut head = [0; 4];
+            File::open(path).unwrap().read_exact(&mut head).unwrap();
+            assert_eq!(Compression::from_magic_bytes(&head), Some(compression));
+
+            let mut content = String::new();
+            open_reader(path).unwrap().read_to_string(&mut content).unwrap();
+            assert_eq!(content, "a,b\n1,2\n");
+        }
+
+        // Un CSV dont l'en-tête commence par `BZh` n'est pas du bzip2
+        assert_eq!(Compression::from_magic_bytes(b"BZh,name\n"), None);
+    }
+}
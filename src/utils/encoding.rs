@@ -0,0 +1,190 @@
+use std::io::{self, BufRead, BufReader, Read};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use encoding_rs::{Decoder, DecoderResult, Encoding, UTF_8};
+use serde::Deserialize;
+use crate::models::error::{EtlError, EtlResult};
+
+pub const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+const BUFFER_SIZE: usize = 8 * 1024;
+
+// Encodage des sources texte (csv, json, ndjson). Les libellés sont ceux du
+// WHATWG : `latin1` et `iso-8859-1` désignent windows-1252.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct EncodingOptions {
+    #[serde(default)]
+    pub encoding: Option<String>,
+    // Remplace les octets invalides par U+FFFD. Sinon la lecture s'arrête au premier
+    // octet invalide sur une erreur `InvalidSource` ; seul un CSV en UTF-8 lu sans
+    // transcodage rejette la ligne et continue
+    #[serde(default)]
+    pub lossy_encoding: bool,
+}
+
+// Flux transcodé en UTF-8 et compteur de caractères remplacés, partagé avec la source
+pub struct DecodedInput {
+    pub reader: Box<dyn Read + Send>,
+    pub replaced_chars: Arc<AtomicUsize>,
+}
+
+impl EncodingOptions {
+    // Aucun transcodage : le parser lit les octets du fichier, BOM mis à part
+    pub fn is_plain_utf8(&self) -> bool {
+        !self.lossy_encoding && self.encoding.as_ref()
+            .is_none_or(|label| Encoding::for_label(label.trim().as_bytes()) == Some(UTF_8))
+    }
+
+    // Le BOM est toujours retiré ; un BOM présent prime sur l'encodage déclaré
+    pub fn decode(&self, path: &str, input: Box<dyn Read + Send>) -> EtlResult<DecodedInput> {
+        let encoding = match &self.encoding {
+            Some(label) => Encoding::for_label(label.trim().as_bytes())
+                .ok_or_else(|| EtlError::InvalidRecipe(format!("encodage inconnu `{}`", label)))?,
+            None => UTF_8,
+        };
+        let replaced_chars = Arc::new(AtomicUsize::new(0));
+
+        // Cas courant : UTF-8 strict, on évite la copie et on retire seulement le BOM
+        if encoding == UTF_8 && !self.lossy_encoding {
+            let mut reader = BufReader::new(input);
+            if reader.fill_buf().map_err(|err| EtlError::io(path, err))?.starts_with(UTF8_BOM) {
+                reader.consume(UTF8_BOM.len());
+            }
+            return Ok(DecodedInput { reader: Box::new(reader), replaced_chars });
+        }
+
+        let reader = TranscodingReader {
+            inner: input,
+            decoder: encoding.new_decoder(),
+            lossy: self.lossy_encoding,
+            replaced_chars: replaced_chars.clone(),
+            input: vec![0; BUFFER_SIZE],
+            input_start: 0,
+            input_end: 0,
+            output: vec![0; BUFFER_SIZE * 3 + 3],
+            output_start: 0,
+            output_end: 0,
+            eof: false,
+            finished: false,
+        };
+
+        Ok(DecodedInput { reader: Box::new(reader), replaced_chars })
+    }
+}
+
+struct TranscodingReader<R> {
+    inner: R,
+    decoder: Decoder,
+    lossy: bool,
+    replaced_chars: Arc<AtomicUsize>,
+    input: Vec<u8>,
+    input_start: usize,
+    input_end: usize,
+    output: Vec<u8>,
+    output_start: usize,
+    output_end: usize,
+    eof: bool,
+    finished: bool,
+}
+
+impl<R: Read> TranscodingReader<R> {
+    // Remplit `output` avec au moins un octet, sauf en fin de flux
+    fn fill_output(&mut self) -> io::Result<()> {
+        self.output_start = 0;
+        self.output_end = 0;
+
+        while self.output_end == 0 && !self.finished {
+            if self.input_start == self.input_end && !self.eof {
+                self.input_start = 0;
+                self.input_end = self.inner.read(&mut self.input)?;
+                self.eof = self.input_end == 0;
+            }
+
+            // On garde 3 octets de marge pour un éventuel U+FFFD
+            let limit = self.output.len() - 3;
+            let (result, read, written) = self.decoder.decode_to_utf8_without_replacement(
+                &self.input[self.input_start..self.input_end],
+                &mut self.output[..limit],
+                self.eof,
+            );
+            self.input_start += read;
+            self.output_end = written;
+
+            match result {
+                DecoderResult::InputEmpty => self.finished = self.eof,
+                DecoderResult::OutputFull => {},
+                DecoderResult::Malformed(_, _) if self.lossy => {
+                    self.output[written..written + 3].copy_from_slice("\u{FFFD}".as_bytes());
+                    self.output_end += 3;
+                    self.replaced_chars.fetch_add(1, Ordering::Relaxed);
+                },
+                DecoderResult::Malformed(_, _) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("octets invalides pour l'encodage {}", self.decoder.encoding().name()),
+                    ));
+                },
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for TranscodingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.output_start == self.output_end {
+            self.fill_output()?;
+        }
+
+        let available = &self.output[self.output_start..self.output_end];
+        let count = available.len().min(buf.len());
+        buf[..count].copy_from_slice(&available[..count]);
+        self.output_start += count;
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn decode(options: &EncodingOptions, bytes: &[u8]) -> (io::Result<String>, usize) {
+        let input: Box<dyn Read + Send> = Box::new(Cursor::new(bytes.to_vec()));
+        let mut decoded = options.decode("test", input).unwrap();
+        let mut content = String::new();
+        let result = decoded.reader.read_to_string(&mut content).map(|_| content);
+        (result, decoded.replaced_chars.load(Ordering::Relaxed))
+    }
+
+    #[test]
+    fn test_latin1_bom_and_lossy() {
+        let latin1 = EncodingOptions { encoding: Some("latin1".to_string()), lossy_encoding: false };
+        assert_eq!(decode(&latin1, b"nom\nH\xE9l\xE8ne\n").0.unwrap(), "nom\nHélène\n");
+
+        let utf8 = EncodingOptions::default();
+        assert_eq!(decode(&utf8, b"\xEF\xBB\xBFnom\n").0.unwrap(), "nom\n");
+        assert!(decode(&utf8, b"H\xE9l\xE8ne").0.is_err());
+
+        let lossy = EncodingOptions { encoding: None, lossy_encoding: true };
+        let (content, replaced) = decode(&lossy, b"H\xE9l\xE8ne");
+        assert_eq!(content.unwrap(), "H\u{FFFD}l\u{FFFD}ne");
+        assert_eq!(replaced, 2);
+    }
+
+    #[test]
+    fn test_strict_decode_fails_fast() {
+        use crate::models::csv_dialect::CsvDialect;
+        use crate::models::csv_reader::CsvReader;
+        use crate::models::input::InputPort;
+
+        let path = std::env::temp_dir().join("encoding_strict.csv");
+        std::fs::write(&path, b"nom\nH\x82\nbob\n").unwrap();
+        let path = path.to_str().unwrap();
+
+        let shift_jis = EncodingOptions { encoding: Some("shift_jis".to_string()), lossy_encoding: false };
+        let mut reader = CsvReader::with_options(path, 10, &CsvDialect::default(), &shift_jis).unwrap();
+
+        assert!(matches!(reader.read_chunk(), Err(EtlError::InvalidSource { .. })));
+    }
+}
@@ -1,4 +1,7 @@
 pub mod multi_extract;
 pub mod capitalize;
 pub mod set_user;
-pub mod parse_yaml;
\ No newline at end of file
+pub mod parse_yaml;
+pub mod compression;
+pub mod resolve_paths;
+pub mod encoding;
\ No newline at end of file
@@ -1,17 +1,43 @@
-use std::error::Error;
+use std::collections::HashMap;
+use std::sync::{mpsc, Arc, Mutex, OnceLock};
+use rayon::prelude::*;
+use rayon::ThreadPool;
+use crate::models::csv_dialect::CsvDialect;
 use crate::models::csv_multi_reader::MultiCsvReader;
-use crate::models::pipeline::{Pipeline, PipelineStats};
+use crate::models::error::{EtlError, EtlResult};
+use crate::models::stage::StagedChunks;
+use crate::models::parallel_input::ParallelOptions;
+use crate::models::pipeline::Pipeline;
 use crate::models::stream_pipeline::StreamingPipeline;
+use crate::utils::encoding::EncodingOptions;
+use crate::utils::resolve_paths::{resolve_paths, PathOptions};
 
-pub fn multi_extract(sources: &[&str]) -> Result<Pipeline<csv::StringRecord>, Box<dyn Error>> {
+// `sources` accepte fichiers, répertoires et motifs glob (`./data/*.csv`)
+pub fn multi_extract(sources: &[&str]) -> EtlResult<Pipeline<csv::StringRecord>> {
+    multi_extract_with(sources, &PathOptions::default(), &ParallelOptions::default())
+}
 
-    if sources.is_empty() {
-        return Err("No sources provided".into());
-    }
+// Les fichiers sont lus sur `parallelism` threads ; sans `preserve_order`, ils sont
+// fusionnés dans l'ordre où leur lecture se termine
+pub fn multi_extract_with(sources: &[&str], options: &PathOptions, parallel: &ParallelOptions)
+-> EtlResult<Pipeline<csv::StringRecord>> {
+    let files = resolve_paths(sources, options)?;
+
+    let pool = read_pool(parallel.parallelism.max(1))?;
 
-    let mut pipelines = sources.iter()
-        .map(|source| Pipeline::extract(source))
-        .collect::<Result<Vec<_>, _>>()?;
+    let mut pipelines = pool.install(|| {
+        if parallel.preserve_order {
+            files.par_iter()
+                .map(|file| Pipeline::extract(file))
+                .collect::<Result<Vec<_>, _>>()
+        } else {
+            let (sender, receiver) = mpsc::channel();
+            files.par_iter().for_each_with(sender, |sender, file| {
+                let _ = sender.send(Pipeline::extract(file));
+            });
+            receiver.into_iter().collect::<Result<Vec<_>, _>>()
+        }
+    })?;
 
     let mut result = pipelines.remove(0);
 
@@ -22,17 +48,37 @@ pub fn multi_extract(sources: &[&str]) -> Result<Pipeline<csv::StringRecord>, Bo
     Ok(result)
 }
 
+// Un pool par nombre de threads, créé au premier appel puis réutilisé
+fn read_pool(threads: usize) -> EtlResult<Arc<ThreadPool>> {
+    static POOLS: OnceLock<Mutex<HashMap<usize, Arc<ThreadPool>>>> = OnceLock::new();
 
-pub fn multi_extract_streaming(sources: &[&str], chunk_size: usize)
--> Result<StreamingPipeline<MultiCsvReader, csv::StringRecord>, Box<dyn Error>> {
-    if sources.is_empty() {
-        return Err("No sources provided".into());
+    let mut pools = POOLS.get_or_init(Default::default).lock().unwrap();
+    if let Some(pool) = pools.get(&threads) {
+        return Ok(pool.clone());
     }
 
-    let multi_reader = MultiCsvReader::new(&sources, chunk_size)?;
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build()
+        .map_err(|err| EtlError::InvalidRecipe(format!("pool de lecture: {}", err)))?;
+    let pool = Arc::new(pool);
+    pools.insert(threads, pool.clone());
+    Ok(pool)
+}
 
-    Ok(StreamingPipeline {
-        chunks: multi_reader,
-        stats: PipelineStats::default()
-    })
-}
\ No newline at end of file
+pub fn multi_extract_streaming(sources: &[&str], chunk_size: usize)
+-> EtlResult<StreamingPipeline<StagedChunks<csv::StringRecord>, csv::StringRecord>> {
+    multi_extract_streaming_with(sources, &PathOptions::default(), &ParallelOptions::default(), chunk_size)
+}
+
+pub fn multi_extract_streaming_with(sources: &[&str], options: &PathOptions, parallel: &ParallelOptions, chunk_size: usize)
+-> EtlResult<StreamingPipeline<StagedChunks<csv::StringRecord>, csv::StringRecord>> {
+    let files = resolve_paths(sources, options)?;
+    let paths: Vec<&str> = files.iter().map(|file| file.as_str()).collect();
+
+    let multi_reader = MultiCsvReader::with_options(&paths, chunk_size, &CsvDialect::default(), &EncodingOptions::default(), parallel)?;
+
+    let mut pipeline = StreamingPipeline::from_input(multi_reader);
+    pipeline.stats.set_sources(files);
+    Ok(pipeline)
+}
@@ -1,9 +1,12 @@
 use std::fs;
+use crate::models::error::{EtlError, EtlResult};
 use crate::models::recipe_config::RecipeConfig;
 
-pub fn parse_yaml(path: &str) -> Result<RecipeConfig, serde_yaml::Error> {
-    let yam_content = fs::read_to_string(path).unwrap();
-    let config: RecipeConfig = serde_yaml::from_str(&yam_content)?;
+pub fn parse_yaml(path: &str) -> EtlResult<RecipeConfig> {
+    let yam_content = fs::read_to_string(path)
+        .map_err(|err| EtlError::io(path, err))?;
+    let config: RecipeConfig = serde_yaml::from_str(&yam_content)
+        .map_err(|source| EtlError::Recipe { path: path.to_string(), source })?;
     Ok(config)
 }
 
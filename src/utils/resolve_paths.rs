@@ -0,0 +1,174 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use globset::{Glob, GlobBuilder, GlobSet, GlobSetBuilder};
+use serde::Deserialize;
+use walkdir::WalkDir;
+use crate::models::error::{EtlError, EtlResult};
+
+// Options appliquées aux répertoires et aux motifs glob d'une source
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PathOptions {
+    // Parcourt aussi les sous-répertoires d'un répertoire source
+    #[serde(default)]
+    pub recursive: bool,
+    // Motifs sur le chemin relatif au répertoire parcouru (`*.csv`, `2024/**`)
+    #[serde(default)]
+    pub include: Vec<String>,
+    #[serde(default)]
+    pub exclude: Vec<String>,
+}
+
+// Développe chaque entrée en liste de fichiers : un répertoire donne ses fichiers,
+// un motif glob ses correspondances, un chemin simple est gardé tel quel.
+// Chaque entrée est triée, les doublons sont retirés en gardant le premier.
+pub fn resolve_paths(patterns: &[&str], options: &PathOptions) -> EtlResult<Vec<String>> {
+    if patterns.is_empty() {
+        return Err(EtlError::NoSource);
+    }
+
+    let include = build_set(&options.include)?;
+    let exclude = build_set(&options.exclude)?;
+    let keep = |relative: &Path| {
+        include.as_ref().is_none_or(|set| set.is_match(relative))
+            && exclude.as_ref().is_none_or(|set| !set.is_match(relative))
+    };
+
+    let mut seen = HashSet::new();
+    let mut files = Vec::new();
+
+    for pattern in patterns {
+        let expanded = if is_glob(pattern) {
+            expand_glob(pattern, &keep)?
+        } else if Path::new(pattern).is_dir() {
+            expand_dir(pattern, options.recursive, &keep)?
+        } else {
+            vec![pattern.to_string()]
+        };
+
+        if expanded.is_empty() {
+            return Err(EtlError::InvalidSource {
+                path: pattern.to_string(),
+                reason: "aucun fichier ne correspond".to_string(),
+            });
+        }
+
+        for file in expanded {
+            if seen.insert(file.clone()) {
+                files.push(file);
+            }
+        }
+    }
+
+    Ok(files)
+}
+
+fn is_glob(pattern: &str) -> bool {
+    pattern.contains(['*', '?', '[', '{'])
+}
+
+fn build_set(patterns: &[String]) -> EtlResult<Option<GlobSet>> {
+    if patterns.is_empty() {
+        return Ok(None);
+    }
+
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        builder.add(Glob::new(pattern).map_err(|err| invalid_pattern(pattern, err))?);
+    }
+    builder.build().map(Some).map_err(|err| invalid_pattern(&patterns.join(", "), err))
+}
+
+fn expand_dir(dir: &str, recursive: bool, keep: &impl Fn(&Path) -> bool) -> EtlResult<Vec<String>> {
+    let max_depth = if recursive { usize::MAX } else { 1 };
+    walk(Path::new(dir), max_depth, |path, relative| keep(relative).then(|| path_to_string(path)))
+}
+
+// Le parcours part du plus long préfixe sans méta-caractère du motif
+fn expand_glob(pattern: &str, keep: &impl Fn(&Path) -> bool) -> EtlResult<Vec<String>> {
+    let matcher = GlobBuilder::new(pattern)
+        .literal_separator(true)
+        .build()
+        .map_err(|err| invalid_pattern(pattern, err))?
+        .compile_matcher();
+
+    let components: Vec<&str> = pattern.split('/').collect();
+    let literal = components.iter().take_while(|c| !is_glob(c)).count();
+    let base: PathBuf = components[..literal].join("/").into();
+
+    let max_depth = if pattern.contains("**") { usize::MAX } else { components.len() - literal };
+    let root = if base.as_os_str().is_empty() { PathBuf::from(".") } else { base.clone() };
+
+    walk(&root, max_depth, |path, relative| {
+        // Un motif relatif (`*.csv`) est comparé sans le `./` du parcours
+        let candidate = if base.as_os_str().is_empty() { relative } else { path };
+        (matcher.is_match(candidate) && keep(relative)).then(|| path_to_string(candidate))
+    })
+}
+
+fn walk(root: &Path, max_depth: usize, mut select: impl FnMut(&Path, &Path) -> Option<String>) -> EtlResult<Vec<String>> {
+    let mut files = Vec::new();
+
+    for entry in WalkDir::new(root).min_depth(1).max_depth(max_depth) {
+        let entry = entry.map_err(|err| {
+            let path = err.path().unwrap_or(root).display().to_string();
+            EtlError::io(&path, err.into())
+        })?;
+
+        if !entry.file_type().is_file() {
+            continue;
+        }
+
+        let relative = entry.path().strip_prefix(root).unwrap_or(entry.path());
+        if let Some(file) = select(entry.path(), relative) {
+            files.push(file);
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+fn path_to_string(path: &Path) -> String {
+    path.to_string_lossy().into_owned()
+}
+
+fn invalid_pattern(pattern: &str, err: globset::Error) -> EtlError {
+    EtlError::InvalidRecipe(format!("motif de chemin invalide `{}`: {}", pattern, err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_glob_and_directory() {
+        let root = std::env::temp_dir().join("resolve_paths_test");
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("sub")).unwrap();
+        for file in ["b.csv", "a.csv", "notes.txt", "sub/c.csv", "sub/skip.csv"] {
+            std::fs::write(root.join(file), "x").unwrap();
+        }
+        let root = root.to_str().unwrap();
+
+        let pattern = format!("{}/*.csv", root);
+        let files = resolve_paths(&[pattern.as_str()], &PathOptions::default()).unwrap();
+        assert_eq!(files, vec![format!("{}/a.csv", root), format!("{}/b.csv", root)]);
+
+        let options = PathOptions {
+            recursive: true,
+            include: vec!["*.csv".to_string()],
+            exclude: vec!["**/skip.csv".to_string()],
+        };
+        let explicit = format!("{}/b.csv", root);
+        let files = resolve_paths(&[explicit.as_str(), root], &options).unwrap();
+        assert_eq!(files, vec![
+            format!("{}/b.csv", root),
+            format!("{}/a.csv", root),
+            format!("{}/sub/c.csv", root),
+        ]);
+
+        let pattern = format!("{}/*.json", root);
+        let err = resolve_paths(&[pattern.as_str()], &options).unwrap_err();
+        assert!(matches!(err, EtlError::InvalidSource { .. }));
+    }
+}